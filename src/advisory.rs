@@ -0,0 +1,625 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - advisory.rs
+
+//! AIRMETs, SIGMETs and PIREPs, rated by their hazards and collected into a [`Briefing`] for a position.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use colored::{Color, ColoredString, Colorize};
+use serde::Serialize;
+use serde_json::Value;
+
+pub mod pirep;
+
+use crate::metar::Assessment;
+use crate::metar::units::{Altitude, AltitudeUnit};
+use crate::position::LatLong;
+use pirep::Pirep;
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+/// An AIRMET or SIGMET: a hazard forecast for an area, with its altitude band and validity.
+pub struct Advisory {
+    /// AIRMET or SIGMET.
+    pub kind: AdvisoryKind,
+    /// Forecast hazard.
+    pub hazard: Hazard,
+    /// Start of the validity, if known.
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the validity, if known.
+    pub valid_to: Option<DateTime<Utc>>,
+    /// Polygon of the affected area, empty if not known.
+    pub area: Vec<LatLong>,
+    /// Raw text of the advisory.
+    pub raw: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Kind of weather advisory.
+pub enum AdvisoryKind {
+    /// Airmen's meteorological information, for hazards of moderate intensity.
+    Airmet,
+    /// Significant meteorological information, for hazards to all aircraft.
+    Sigmet,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+/// A hazard reported in a PIREP or forecast in an advisory. The altitude band is None if not given.
+pub struct Hazard {
+    /// Type of hazard, e.g. turbulence or icing.
+    pub kind: HazardType,
+    /// Intensity of the hazard, if given.
+    pub intensity: Option<Intensity>,
+    /// Lower limit of the altitude band.
+    pub floor: Option<Altitude>,
+    /// Upper limit of the altitude band.
+    pub ceiling: Option<Altitude>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Type of hazard to aircraft.
+pub enum HazardType {
+    /// Icing (ICE).
+    Icing,
+    /// Turbulence (TURB).
+    Turbulence,
+    /// Thunderstorms (TS).
+    Thunderstorm,
+    /// Mountain waves (MTW).
+    MountainWave,
+    /// Mountains obscured by clouds or precipitation (MT OBSC).
+    MountainObscuration,
+    /// Ceilings below 1000ft or visibility below 3SM over a wide area (IFR).
+    Ifr,
+    /// Volcanic ash (VA).
+    VolcanicAsh,
+    /// Tropical cyclone (TC).
+    TropicalCyclone,
+    /// Dust or sand storm (DS, SS).
+    DustStorm,
+    /// Any other hazard.
+    Other,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Intensity of icing or turbulence.
+pub enum Intensity {
+    /// Light (LGT).
+    Light,
+    /// Moderate (MOD).
+    Moderate,
+    /// Severe (SEV).
+    Severe,
+    /// Extreme (EXTM).
+    Extreme,
+}
+
+#[derive(Debug, Serialize)]
+/// A report together with its assessment, for JSON output.
+pub struct AssessedReport<T> {
+    /// The report itself.
+    #[serde(flatten)]
+    pub report: T,
+    /// Its assessment.
+    pub assessment: Assessment,
+}
+
+/// PIREPs older than this are left out of a briefing.
+pub const PIREP_MAX_AGE: TimeDelta = TimeDelta::hours(2);
+
+#[derive(Debug, Serialize)]
+/// The advisories and pilot reports affecting a position.
+pub struct Briefing {
+    /// AIRMETs and SIGMETs whose area contains the position.
+    pub advisories: Vec<AssessedReport<Advisory>>,
+    /// Recent PIREPs near the position.
+    pub pireps: Vec<AssessedReport<Pirep>>,
+}
+
+impl Advisory {
+    /// Parses an AIRMET or SIGMET as returned by `AvWx`. The hazard is taken from the decoded observation, the intensity from the hazard description or the raw text.
+    #[must_use]
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let raw = json.get("raw")?.as_str()?.to_string();
+        let kind_repr = json.get("type").and_then(Value::as_str).unwrap_or(&raw);
+        let kind = if kind_repr.to_uppercase().contains("SIGMET") {
+            AdvisoryKind::Sigmet
+        } else {
+            AdvisoryKind::Airmet
+        };
+
+        let observation = json.get("observation").unwrap_or(json);
+        let description = observation
+            .get("type")
+            .map(|hazard| {
+                ["repr", "value"]
+                    .iter()
+                    .filter_map(|key| hazard.get(key).and_then(Value::as_str))
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+        let hazard = Hazard {
+            kind: hazard_type_from_text(&description)
+                .or_else(|| hazard_type_from_text(&raw))
+                .unwrap_or(HazardType::Other),
+            intensity: intensity_from_text(&description).or_else(|| intensity_from_text(&raw)),
+            floor: altitude_from_json(observation.get("floor")),
+            ceiling: altitude_from_json(observation.get("ceiling")),
+        };
+
+        let area = observation
+            .get("coords")
+            .or_else(|| json.get("coords"))
+            .and_then(Value::as_array)
+            .map(|coords| {
+                coords
+                    .iter()
+                    .filter_map(|coord| {
+                        Some(LatLong(
+                            coord.get("lat")?.as_f64()?,
+                            coord.get("lon")?.as_f64()?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Advisory {
+            kind,
+            hazard,
+            valid_from: time_from_json(json.get("start_time")),
+            valid_to: time_from_json(json.get("end_time")),
+            area,
+            raw,
+        })
+    }
+
+    /// True if the position lies within the area of the advisory. Advisories without a known area are assumed to affect the position, as `AvWx` only returns advisories for the requested location.
+    #[must_use]
+    pub fn affects(&self, position: &LatLong) -> bool {
+        self.area.len() < 3 || polygon_contains(&self.area, position)
+    }
+
+    /// True if the advisory has not expired at `time`.
+    #[must_use]
+    pub fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        self.valid_to.is_none_or(|valid_to| time < valid_to)
+            && self.valid_from.is_none_or(|valid_from| valid_from <= time)
+    }
+
+    /// SIGMETs are rated bad, AIRMETs at least marginal.
+    #[must_use]
+    pub fn assess(&self) -> Assessment {
+        let kind_assessment = match self.kind {
+            AdvisoryKind::Sigmet => Assessment::Bad,
+            AdvisoryKind::Airmet => Assessment::Marginal,
+        };
+        kind_assessment.max(self.hazard.assess())
+    }
+
+    /// Kind, hazard and validity of the advisory, coloured by its assessment.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let mut output: ColoredString = match self.kind {
+            AdvisoryKind::Sigmet => self.kind.to_string().bold().bright_white().on_red(),
+            AdvisoryKind::Airmet => self.kind.to_string().bold().black().on_yellow(),
+        };
+        output = format!("{output} {}", self.hazard.colourise()).into();
+        if self.valid_from.is_some() || self.valid_to.is_some() {
+            let format_time = |time: Option<DateTime<Utc>>| {
+                time.map_or("?".to_string(), |time| time.format("%d%H%MZ").to_string())
+            };
+            let validity = format!(
+                "{}-{}",
+                format_time(self.valid_from),
+                format_time(self.valid_to)
+            );
+            output = format!("{output} {}", validity.white()).into();
+        }
+        output
+    }
+}
+
+impl Hazard {
+    /// Thunderstorms, volcanic ash and tropical cyclones are always rated bad, other hazards by their intensity.
+    #[must_use]
+    pub fn assess(&self) -> Assessment {
+        match self.kind {
+            HazardType::Thunderstorm | HazardType::VolcanicAsh | HazardType::TropicalCyclone => {
+                Assessment::Bad
+            }
+            _ => match self.intensity {
+                Some(Intensity::Severe | Intensity::Extreme) => Assessment::Bad,
+                Some(Intensity::Moderate) => Assessment::Marginal,
+                Some(Intensity::Light) | None => Assessment::Good,
+            },
+        }
+    }
+
+    /// The hazard, coloured by its assessment.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        self.to_string().color(Color::from(self.assess()))
+    }
+}
+
+impl Briefing {
+    /// Collects the advisories valid at `time` and affecting the position, if known, as well as the PIREPs made within [`PIREP_MAX_AGE`] before `time`. PIREPs are not filtered by location or altitude, as their location is given relative to a navaid; they are expected to be requested for the vicinity of the position.
+    #[must_use]
+    pub fn new(
+        advisories: Vec<Advisory>,
+        pireps: Vec<Pirep>,
+        position: Option<&LatLong>,
+        time: DateTime<Utc>,
+    ) -> Self {
+        let advisories = advisories
+            .into_iter()
+            .filter(|advisory| advisory.is_valid_at(time))
+            .filter(|advisory| position.is_none_or(|position| advisory.affects(position)))
+            .map(|advisory| AssessedReport {
+                assessment: advisory.assess(),
+                report: advisory,
+            })
+            .collect();
+        let pireps = pireps
+            .into_iter()
+            .filter(|pirep| {
+                pirep
+                    .time
+                    .is_none_or(|reported| time - reported <= PIREP_MAX_AGE)
+            })
+            .map(|pirep| AssessedReport {
+                assessment: pirep.assess(),
+                report: pirep,
+            })
+            .collect();
+        Briefing { advisories, pireps }
+    }
+
+    /// Renders one line per advisory and PIREP, or a note if there are none.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let lines: Vec<String> = self
+            .advisories
+            .iter()
+            .map(|advisory| advisory.report.colourise().to_string())
+            .chain(
+                self.pireps
+                    .iter()
+                    .map(|pirep| pirep.report.colourise().to_string()),
+            )
+            .collect();
+        if lines.is_empty() {
+            return "No AIRMETs, SIGMETs or PIREPs for this position.".green();
+        }
+        lines.join("\n").into()
+    }
+}
+
+impl Display for AdvisoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdvisoryKind::Airmet => write!(f, "AIRMET"),
+            AdvisoryKind::Sigmet => write!(f, "SIGMET"),
+        }
+    }
+}
+
+impl Display for Hazard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(intensity) = self.intensity {
+            write!(f, "{intensity} ")?;
+        }
+        write!(f, "{}", self.kind)?;
+        match (self.floor, self.ceiling) {
+            (Some(floor), Some(ceiling)) => {
+                write!(f, " {}-{}", format_level(floor), format_level(ceiling))
+            }
+            (Some(floor), None) => write!(f, " ABV {}", format_level(floor)),
+            (None, Some(ceiling)) => write!(f, " BLO {}", format_level(ceiling)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Display for HazardType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr = match self {
+            HazardType::Icing => "ICE",
+            HazardType::Turbulence => "TURB",
+            HazardType::Thunderstorm => "TS",
+            HazardType::MountainWave => "MTW",
+            HazardType::MountainObscuration => "MT OBSC",
+            HazardType::Ifr => "IFR",
+            HazardType::VolcanicAsh => "VA",
+            HazardType::TropicalCyclone => "TC",
+            HazardType::DustStorm => "DS",
+            HazardType::Other => "OTHER",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl FromStr for HazardType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "ICE" | "ICG" | "ICING" => Ok(Self::Icing),
+            "TURB" | "TB" | "TURBULENCE" => Ok(Self::Turbulence),
+            "TS" | "TSGR" | "THUNDERSTORM" | "THUNDERSTORMS" | "CONVECTIVE" => {
+                Ok(Self::Thunderstorm)
+            }
+            "MTW" => Ok(Self::MountainWave),
+            "MT" | "MTN" | "MTNS" => Ok(Self::MountainObscuration),
+            "IFR" => Ok(Self::Ifr),
+            "VA" | "ASH" => Ok(Self::VolcanicAsh),
+            "TC" | "CYCLONE" => Ok(Self::TropicalCyclone),
+            "DS" | "SS" | "DUSTSTORM" | "SANDSTORM" => Ok(Self::DustStorm),
+            _ => Err(anyhow!("Invalid hazard type {s}.")),
+        }
+    }
+}
+
+impl Display for Intensity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr = match self {
+            Intensity::Light => "LGT",
+            Intensity::Moderate => "MOD",
+            Intensity::Severe => "SEV",
+            Intensity::Extreme => "EXTM",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl FromStr for Intensity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "LGT" | "LIGHT" => Ok(Self::Light),
+            "MOD" | "MODERATE" => Ok(Self::Moderate),
+            "SEV" | "SEVERE" => Ok(Self::Severe),
+            "EXTM" | "EXTRM" | "EXTREME" => Ok(Self::Extreme),
+            _ => Err(anyhow!("Invalid intensity {s}.")),
+        }
+    }
+}
+
+/// Words of a free text description, e.g. "SEV ICE" or "LGT-MOD".
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// The first hazard type mentioned in a description.
+pub(crate) fn hazard_type_from_text(text: &str) -> Option<HazardType> {
+    words(text).find_map(|word| word.parse().ok())
+}
+
+/// The highest intensity mentioned in a description, so that "LGT-MOD" counts as moderate.
+pub(crate) fn intensity_from_text(text: &str) -> Option<Intensity> {
+    words(text).filter_map(|word| word.parse().ok()).max()
+}
+
+/// Formats an altitude as a flight level, or SFC for the surface.
+pub(crate) fn format_level(altitude: Altitude) -> String {
+    let hectofeet = altitude.convert(AltitudeUnit::Ft).value() / 100;
+    if hectofeet == 0 {
+        "SFC".to_string()
+    } else {
+        format!("FL{hectofeet:03}")
+    }
+}
+
+/// Reads an altitude in the `AvWx` number format. Values of flight levels are given in hundreds of feet, all others in feet.
+fn altitude_from_json(json: Option<&Value>) -> Option<Altitude> {
+    let json = json?;
+    let repr = json.get("repr").and_then(Value::as_str).unwrap_or_default();
+    if repr == "SFC" {
+        return Some(Altitude::new(0, AltitudeUnit::Ft));
+    }
+    let value = json.get("value")?.as_f64()?;
+    #[allow(clippy::cast_possible_truncation)]
+    let value = value.round() as i64;
+    if repr.starts_with("FL") {
+        Some(Altitude::new(value * 100, AltitudeUnit::Ft))
+    } else {
+        Some(Altitude::new(value, AltitudeUnit::Ft))
+    }
+}
+
+/// Reads a time in the `AvWx` format, e.g. {"dt": "2024-06-21T12:00:00Z"}.
+pub(crate) fn time_from_json(json: Option<&Value>) -> Option<DateTime<Utc>> {
+    let dt = json?.get("dt")?.as_str()?;
+    DateTime::parse_from_rfc3339(dt)
+        .ok()
+        .map(|time| time.to_utc())
+}
+
+/// Iterates over the reports of an `AvWx` response, which may be an array, an object with a "reports" array or a single report.
+#[must_use]
+pub fn json_reports(json: &Value) -> Vec<&Value> {
+    match json.get("reports").unwrap_or(json) {
+        Value::Array(reports) => reports.iter().collect(),
+        report => vec![report],
+    }
+}
+
+/// Ray casting test whether the point lies inside the polygon, treating latitude and longitude as plane coordinates.
+fn polygon_contains(polygon: &[LatLong], point: &LatLong) -> bool {
+    let mut inside = false;
+    let edges = polygon.iter().zip(polygon.iter().cycle().skip(1));
+    for (LatLong(lat_a, lon_a), LatLong(lat_b, lon_b)) in edges {
+        if (*lat_a > point.0) != (*lat_b > point.0)
+            && point.1 < (lon_b - lon_a) * (point.0 - lat_a) / (lat_b - lat_a) + lon_a
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const SIGMET: &str = r#"{
+        "raw": "WSUS32 KKCI 211655 SIGE SIGMET 5E VALID UNTIL 211855Z FROM 30SSW IND-20NE LOU-40SE IND-30SSW IND SEV TURB BTN FL180 AND FL240",
+        "type": "SIGMET",
+        "start_time": {"repr": "211655", "dt": "2024-06-21T16:55:00Z"},
+        "end_time": {"repr": "211855", "dt": "2024-06-21T18:55:00Z"},
+        "observation": {
+            "type": {"repr": "SEV TURB", "value": "Severe turbulence"},
+            "floor": {"repr": "FL180", "value": 180},
+            "ceiling": {"repr": "FL240", "value": 240},
+            "coords": [
+                {"lat": 39.3, "lon": -86.5},
+                {"lat": 38.5, "lon": -85.4},
+                {"lat": 39.2, "lon": -85.7}
+            ]
+        }
+    }"#;
+
+    fn sigmet() -> Advisory {
+        Advisory::from_json(&serde_json::from_str(SIGMET).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_advisory_from_json() {
+        let advisory = sigmet();
+        assert_eq!(advisory.kind, AdvisoryKind::Sigmet);
+        let expected = Hazard {
+            kind: HazardType::Turbulence,
+            intensity: Some(Intensity::Severe),
+            floor: Some(Altitude::new(18000, AltitudeUnit::Ft)),
+            ceiling: Some(Altitude::new(24000, AltitudeUnit::Ft)),
+        };
+        assert_eq!(advisory.hazard, expected);
+        assert_eq!(advisory.area.len(), 3);
+        assert_eq!(
+            advisory.valid_to,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 18, 55, 0).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_advisory_serialize() {
+        let json = serde_json::to_value(sigmet()).unwrap();
+        assert_eq!(json["kind"], "sigmet");
+        assert_eq!(json["hazard"]["kind"], "turbulence");
+    }
+
+    #[tokio::test]
+    async fn test_advisory_from_json_raw_only() {
+        let json: Value =
+            serde_json::from_str(r#"{"raw": "AIRMET ZULU FOR ICE AND FRZLVL VALID UNTIL 212100 MOD ICE BTN FRZLVL AND FL200"}"#)
+                .unwrap();
+        let advisory = Advisory::from_json(&json).unwrap();
+        assert_eq!(advisory.kind, AdvisoryKind::Airmet);
+        assert_eq!(advisory.hazard.kind, HazardType::Icing);
+        assert_eq!(advisory.hazard.intensity, Some(Intensity::Moderate));
+        assert!(advisory.affects(&LatLong(0.0, 0.0)));
+        assert_eq!(advisory.assess(), Assessment::Marginal);
+    }
+
+    #[tokio::test]
+    async fn test_advisory_affects() {
+        let advisory = sigmet();
+        assert!(advisory.affects(&LatLong(39.0, -85.8)));
+        assert!(!advisory.affects(&LatLong(40.0, -86.0)));
+    }
+
+    #[tokio::test]
+    async fn test_advisory_is_valid_at() {
+        let advisory = sigmet();
+        assert!(advisory.is_valid_at(Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap()));
+        assert!(!advisory.is_valid_at(Utc.with_ymd_and_hms(2024, 6, 21, 19, 0, 0).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_hazard_display() {
+        assert_eq!(sigmet().hazard.to_string(), "SEV TURB FL180-FL240");
+        let hazard = Hazard {
+            kind: HazardType::Icing,
+            intensity: None,
+            floor: None,
+            ceiling: Some(Altitude::new(8000, AltitudeUnit::Ft)),
+        };
+        assert_eq!(hazard.to_string(), "ICE BLO FL080");
+    }
+
+    #[tokio::test]
+    async fn test_text_parsing() {
+        assert_eq!(
+            hazard_type_from_text("OBSC TS TOPS"),
+            Some(HazardType::Thunderstorm)
+        );
+        assert_eq!(
+            hazard_type_from_text("MT OBSC"),
+            Some(HazardType::MountainObscuration)
+        );
+        assert_eq!(hazard_type_from_text("NONE"), None);
+        assert_eq!(
+            intensity_from_text("LGT-MOD RIME"),
+            Some(Intensity::Moderate)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_reports() {
+        let json: Value =
+            serde_json::from_str(&format!("{{\"reports\": [{SIGMET}, {SIGMET}]}}")).unwrap();
+        assert_eq!(json_reports(&json).len(), 2);
+        let json: Value = serde_json::from_str(SIGMET).unwrap();
+        assert_eq!(json_reports(&json).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_briefing() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap();
+        let briefing = Briefing::new(
+            vec![sigmet()],
+            Vec::new(),
+            Some(&LatLong(39.0, -85.8)),
+            time,
+        );
+        assert_eq!(briefing.advisories.len(), 1);
+        assert_eq!(briefing.advisories[0].assessment, Assessment::Bad);
+        let briefing = Briefing::new(vec![sigmet()], Vec::new(), Some(&LatLong(50.0, 7.0)), time);
+        assert!(briefing.advisories.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_briefing_pirep_age() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap();
+        let pirep = |tm: &str| {
+            Pirep::from_raw_at(&format!("DEN UA /OV DEN090030/TM {tm}/FL080/TB MOD"), time).unwrap()
+        };
+        let pireps = vec![pirep("1535"), pirep("1455"), pirep("0900")];
+        let briefing = Briefing::new(Vec::new(), pireps, None, time);
+        assert_eq!(briefing.pireps.len(), 1);
+        assert_eq!(
+            briefing.pireps[0].report.time,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 15, 35, 0).unwrap())
+        );
+    }
+}
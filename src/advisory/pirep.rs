@@ -0,0 +1,264 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - advisory/pirep.rs
+
+//! Pilot reports (PIREPs) of turbulence, icing and other conditions encountered in flight.
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use colored::{ColoredString, Colorize};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{Hazard, HazardType, format_level, intensity_from_text, time_from_json};
+use crate::metar::units::{Altitude, AltitudeUnit};
+use crate::metar::{Assessment, build_regex};
+
+/// Altitude band of a hazard like "060-080", "BLO 130" or "ABV 100".
+static BAND_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(r"(?<floor>\d{3})-(?<ceiling>\d{3})|BLO (?<below>\d{3})|ABV (?<above>\d{3})")
+});
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+/// A pilot report (PIREP) of the conditions encountered in flight.
+pub struct Pirep {
+    /// True for urgent reports (UUA).
+    pub urgent: bool,
+    /// Location of the report, usually as a radial and distance from a navaid (OV).
+    pub location: Option<String>,
+    /// Time of the report (TM).
+    pub time: Option<DateTime<Utc>>,
+    /// Flight level or altitude (FL).
+    pub altitude: Option<Altitude>,
+    /// Aircraft type (TP).
+    pub aircraft: Option<String>,
+    /// Reported turbulence and icing.
+    pub hazards: Vec<Hazard>,
+    /// Raw text of the report.
+    pub raw: String,
+}
+
+impl Pirep {
+    /// Parses a PIREP as returned by `AvWx`, using the report time to resolve the time of the report.
+    #[must_use]
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let raw = json.get("raw")?.as_str()?;
+        let reference = time_from_json(json.get("time")).unwrap_or_else(Utc::now);
+        Self::from_raw_at(raw, reference)
+    }
+
+    /// Parses a PIREP in the standard format, e.g. "DEN UA /OV DEN090030/TM 1535/FL080/TP C172/TB LGT-MOD CHOP 060-080". The time of the report is placed on the most recent day for which it is not after `reference`. Returns None if the report type UA or UUA is missing.
+    #[must_use]
+    pub fn from_raw_at(raw: &str, reference: DateTime<Utc>) -> Option<Self> {
+        let mut groups = raw.split('/');
+        let header: Vec<&str> = groups.next()?.split_whitespace().collect();
+        let urgent = header.contains(&"UUA");
+        if !urgent && !header.contains(&"UA") {
+            return None;
+        }
+
+        let mut pirep = Pirep {
+            urgent,
+            location: None,
+            time: None,
+            altitude: None,
+            aircraft: None,
+            hazards: Vec::new(),
+            raw: raw.to_string(),
+        };
+        for group in groups.map(str::trim) {
+            let (Some(prefix), Some(content)) = (group.get(..2), group.get(2..)) else {
+                continue;
+            };
+            let content = content.trim();
+            match prefix {
+                "OV" => pirep.location = Some(content.to_string()),
+                "TM" => pirep.time = time_from_str(content, reference),
+                "FL" => {
+                    pirep.altitude = content
+                        .parse::<i64>()
+                        .ok()
+                        .map(|hectofeet| Altitude::new(hectofeet * 100, AltitudeUnit::Ft));
+                }
+                "TP" => pirep.aircraft = Some(content.to_string()),
+                "TB" => pirep
+                    .hazards
+                    .extend(hazard_from_str(HazardType::Turbulence, content)),
+                "IC" => pirep
+                    .hazards
+                    .extend(hazard_from_str(HazardType::Icing, content)),
+                _ => {}
+            }
+        }
+        Some(pirep)
+    }
+
+    /// Urgent reports are rated bad, all others by their worst hazard.
+    #[must_use]
+    pub fn assess(&self) -> Assessment {
+        let hazards = self
+            .hazards
+            .iter()
+            .map(Hazard::assess)
+            .max()
+            .unwrap_or(Assessment::Good);
+        if self.urgent {
+            Assessment::Bad
+        } else {
+            hazards
+        }
+    }
+
+    /// The decoded report, with the report type and each hazard coloured by its assessment.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let mut output: ColoredString = if self.urgent {
+            "UUA".bold().bright_white().on_red()
+        } else {
+            "UA".bold()
+        };
+        let details = [
+            self.location.clone(),
+            self.time.map(|time| time.format("%H%MZ").to_string()),
+            self.altitude.map(format_level),
+            self.aircraft.clone(),
+        ];
+        for detail in details.into_iter().flatten() {
+            output = format!("{output} {}", detail.white()).into();
+        }
+        for hazard in &self.hazards {
+            output = format!("{output} {}", hazard.colourise()).into();
+        }
+        output
+    }
+}
+
+/// Parses the content of a TB or IC group, e.g. "LGT-MOD CHOP 060-080" or "MOD RIME BLO 050". Returns None for negative reports (NEG).
+fn hazard_from_str(kind: HazardType, repr: &str) -> Option<Hazard> {
+    if repr.split_whitespace().any(|word| word == "NEG") {
+        return None;
+    }
+    let level = |name: &str, matches: &regex::Captures| {
+        matches
+            .name(name)
+            .and_then(|level| level.as_str().parse::<i64>().ok())
+            .map(|hectofeet| Altitude::new(hectofeet * 100, AltitudeUnit::Ft))
+    };
+    let (floor, ceiling) = BAND_REGEX.captures(repr).map_or((None, None), |matches| {
+        (
+            level("floor", &matches).or_else(|| level("above", &matches)),
+            level("ceiling", &matches).or_else(|| level("below", &matches)),
+        )
+    });
+    Some(Hazard {
+        kind,
+        intensity: intensity_from_text(repr),
+        floor,
+        ceiling,
+    })
+}
+
+/// Parses a time like "1535". The time is placed on the most recent day for which it is not after `reference`.
+fn time_from_str(repr: &str, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let hour: u32 = repr.get(..2)?.parse().ok()?;
+    let minute: u32 = repr.get(2..4)?.parse().ok()?;
+    let time = reference
+        .date_naive()
+        .and_hms_opt(hour, minute, 0)?
+        .and_utc();
+    if time > reference {
+        Some(time - TimeDelta::days(1))
+    } else {
+        Some(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::advisory::Intensity;
+
+    const RAW: &str = "DEN UA /OV DEN090030/TM 1535/FL080/TP C172/SK BKN040/TA M05/TB LGT-MOD CHOP 060-080/IC NEG/RM SMOOTH ABV 100";
+
+    fn reference() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 21, 16, 0, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pirep_from_raw() {
+        let pirep = Pirep::from_raw_at(RAW, reference()).unwrap();
+        assert!(!pirep.urgent);
+        assert_eq!(pirep.location, Some("DEN090030".to_string()));
+        assert_eq!(
+            pirep.time,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 15, 35, 0).unwrap())
+        );
+        assert_eq!(pirep.altitude, Some(Altitude::new(8000, AltitudeUnit::Ft)));
+        assert_eq!(pirep.aircraft, Some("C172".to_string()));
+        let expected = vec![Hazard {
+            kind: HazardType::Turbulence,
+            intensity: Some(Intensity::Moderate),
+            floor: Some(Altitude::new(6000, AltitudeUnit::Ft)),
+            ceiling: Some(Altitude::new(8000, AltitudeUnit::Ft)),
+        }];
+        assert_eq!(pirep.hazards, expected);
+        assert_eq!(pirep.assess(), Assessment::Marginal);
+    }
+
+    #[tokio::test]
+    async fn test_pirep_urgent() {
+        let pirep = Pirep::from_raw_at(
+            "UUA /OV OKC180010/TM 2350/FL120/TP B738/IC SEV CLR BLO 130",
+            reference(),
+        )
+        .unwrap();
+        assert!(pirep.urgent);
+        assert_eq!(
+            pirep.time,
+            Some(Utc.with_ymd_and_hms(2024, 6, 20, 23, 50, 0).unwrap())
+        );
+        assert_eq!(pirep.hazards[0].intensity, Some(Intensity::Severe));
+        assert_eq!(
+            pirep.hazards[0].ceiling,
+            Some(Altitude::new(13000, AltitudeUnit::Ft))
+        );
+        assert_eq!(pirep.assess(), Assessment::Bad);
+    }
+
+    #[tokio::test]
+    async fn test_pirep_invalid() {
+        assert!(Pirep::from_raw_at("EDDK 211250Z 27012KT", reference()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pirep_from_json() {
+        let json: Value = serde_json::from_str(&format!(
+            "{{\"raw\": \"{RAW}\", \"time\": {{\"dt\": \"2024-06-21T15:35:00Z\"}}}}"
+        ))
+        .unwrap();
+        let pirep = Pirep::from_json(&json).unwrap();
+        assert_eq!(
+            pirep.time,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 15, 35, 0).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pirep_colourise() {
+        let pirep = Pirep::from_raw_at(RAW, reference()).unwrap();
+        let output = pirep.colourise().clear().to_string();
+        assert_eq!(output, "UA DEN090030 1535Z FL080 C172 MOD TURB FL060-FL080");
+    }
+}
@@ -10,16 +10,28 @@
 // limitations under the License.
 // WxFetch - api.rs
 
+//! Requests to the `AvWx` API for weather reports, forecasts, advisories, NOTAMs and station data. Failed requests are logged and return None.
+
 use log::error;
 use reqwest::{Client, Error, Response};
 use serde_json::Value;
 
-use crate::{Config, Secrets};
+use crate::{Config, LatLong, Position, Secrets};
 
 /// Given a Config and Secrets, sends a request to fetch a METAR and returns the report in JSON format wrapped in Some if successful, None otherwise.
 pub async fn request_wx(config: &Config, secrets: &Secrets) -> Option<Value> {
+    request_report("metar", config, secrets).await
+}
+
+/// Given a Config and Secrets, sends a request to fetch a TAF and returns the forecast in JSON format wrapped in Some if successful, None otherwise.
+pub async fn request_taf(config: &Config, secrets: &Secrets) -> Option<Value> {
+    request_report("taf", config, secrets).await
+}
+
+/// Requests a report of the given kind ("metar" or "taf") for the position in the Config, falling back to the nearest reporting station.
+async fn request_report(report: &str, config: &Config, secrets: &Secrets) -> Option<Value> {
     let position = config.position.get_location_str().await;
-    let resp = send_api_call(position, secrets).await.ok()?;
+    let resp = send_api_call(report, position, secrets).await.ok()?;
     let status = resp.status().as_u16();
 
     if status == 200 {
@@ -28,7 +40,7 @@ pub async fn request_wx(config: &Config, secrets: &Secrets) -> Option<Value> {
         error!("Weather request failed. Provide a valid AvWx API key.");
         None
     } else if let Some(nearest_station_code) = get_nearest_station(config, secrets).await {
-        send_api_call(nearest_station_code, secrets)
+        send_api_call(report, nearest_station_code, secrets)
             .await
             .ok()?
             .json::<Value>()
@@ -40,9 +52,13 @@ pub async fn request_wx(config: &Config, secrets: &Secrets) -> Option<Value> {
     }
 }
 
-/// Given a properly formattet position string and Secrets, requests METAR from avwx and wraps the Response in a Result.
-async fn send_api_call(position: String, secrets: &Secrets) -> Result<Response, Error> {
-    let uri = format!("https://avwx.rest/api/metar/{position}?onfail=nearest&options=info");
+/// Given a report kind, a properly formattet position string and Secrets, requests the report from avwx and wraps the Response in a Result.
+async fn send_api_call(
+    report: &str,
+    position: String,
+    secrets: &Secrets,
+) -> Result<Response, Error> {
+    let uri = format!("https://avwx.rest/api/{report}/{position}?onfail=nearest&options=info");
     let resp: Response = Client::new()
         .get(uri)
         .header("Authorization", format!("BEARER {}", secrets.avwx_api_key))
@@ -51,6 +67,81 @@ async fn send_api_call(position: String, secrets: &Secrets) -> Result<Response,
     Ok(resp)
 }
 
+/// Given a Config and Secrets, requests the pilot reports near the position and returns them in JSON format wrapped in Some if successful, None otherwise.
+pub async fn request_pireps(config: &Config, secrets: &Secrets) -> Option<Value> {
+    let position = config.position.get_location_str().await;
+    get_json(format!("https://avwx.rest/api/pirep/{position}"), secrets).await
+}
+
+/// Given a Config and Secrets, requests the AIRMETs and SIGMETs for the position and returns them in JSON format wrapped in Some if successful, None otherwise.
+pub async fn request_advisories(config: &Config, secrets: &Secrets) -> Option<Value> {
+    let position = config.position.get_location_str().await;
+    get_json(
+        format!("https://avwx.rest/api/airsigmet/{position}"),
+        secrets,
+    )
+    .await
+}
+
+/// `AvWx` endpoint for NOTAMs, the default for [`request_notams`].
+pub const AVWX_NOTAM_URL: &str = "https://avwx.rest/api/notam";
+
+/// Given a station code, Secrets and the URL of a NOTAM endpoint like [`AVWX_NOTAM_URL`], requests the NOTAMs for the station and returns them in JSON format wrapped in Some if successful, None otherwise. The URL can point to a local mock of the `AvWx` API.
+pub async fn request_notams(station: &str, secrets: &Secrets, url: &str) -> Option<Value> {
+    let url = url.trim_end_matches('/');
+    get_json(format!("{url}/{station}"), secrets).await
+}
+
+/// Returns the ICAO code of the airfield in the Config, or the nearest reporting station for other positions.
+pub async fn get_station(config: &Config, secrets: &Secrets) -> Option<String> {
+    match &config.position {
+        Position::Airfield(icao) => Some(icao.clone()),
+        _ => get_nearest_station(config, secrets).await,
+    }
+}
+
+/// Returns the coordinates of the position in the Config. For airfields these are looked up from avwx.
+pub async fn get_coordinates(config: &Config, secrets: &Secrets) -> Option<LatLong> {
+    if let Position::LatLong(latlong) = &config.position {
+        return Some(latlong.clone());
+    }
+    let location = config.position.get_location_str().await;
+    if let Some((lat, lon)) = location.split_once(',')
+        && let (Ok(lat), Ok(lon)) = (lat.parse(), lon.parse())
+    {
+        return Some(LatLong(lat, lon));
+    }
+    let station = get_json(
+        format!("https://avwx.rest/api/station/{location}?filter=latitude,longitude"),
+        secrets,
+    )
+    .await?;
+    Some(LatLong(
+        station.get("latitude")?.as_f64()?,
+        station.get("longitude")?.as_f64()?,
+    ))
+}
+
+/// Sends a GET request to avwx and returns the response body in JSON format wrapped in Some if successful, None otherwise. Error responses are logged and return None.
+async fn get_json(uri: String, secrets: &Secrets) -> Option<Value> {
+    let resp = Client::new()
+        .get(&uri)
+        .header("Authorization", format!("BEARER {}", secrets.avwx_api_key))
+        .send()
+        .await
+        .ok()?;
+    let status = resp.status();
+    if status.as_u16() == 401 {
+        error!("Weather request failed. Provide a valid AvWx API key.");
+        return None;
+    }
+    if !status.is_success() {
+        error!("Request to {uri} failed with status {status}.");
+        return None;
+    }
+    resp.json().await.ok()
+}
+
 /// For a given Position in a Config as well as the necessary Secrets returns a String with the ICAO (or similar) code for the nearest reporting station.
 async fn get_nearest_station(config: &Config, secrets: &Secrets) -> Option<String> {
     let uri = format!(
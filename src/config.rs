@@ -10,133 +10,210 @@
 // limitations under the License.
 // WxFetch - config.rs
 
-use std::{fs::File, io::Read};
+//! Configuration of the position, personal minima and display units, read from a TOML file.
 
+use std::{fs::File, io::Read, str::FromStr};
+
+use anyhow::Context;
 use chrono::TimeDelta;
 use toml::{Table, Value};
 
 use crate::{
+    Secrets,
     api::check_icao_code,
+    metar::units::{
+        Altitude, AltitudeUnit, Distance, DistanceUnit, PressureUnit, Speed, SpeedUnit,
+        Temperature, TemperatureDifference, TemperatureUnit, Units,
+    },
     position::{LatLong, Position},
-    Args, Secrets,
 };
 
 #[derive(PartialEq, Debug)]
+/// Position to fetch weather for, personal minima used to assess reports and display units.
 pub struct Config {
+    /// Position to fetch weather for.
     pub position: Position,
-    pub cloud_minimum: i64,
-    pub cloud_marginal: i64,
-    pub temp_minimum: i64,
-    pub spread_minimum: i64,
+    /// Cloud bases at or below are bad.
+    pub cloud_minimum: Altitude,
+    /// Cloud bases at or below are marginal.
+    pub cloud_marginal: Altitude,
+    /// Temperatures at or below are bad.
+    pub temp_minimum: Temperature,
+    /// Spreads between temperature and dewpoint at or below are bad.
+    pub spread_minimum: TemperatureDifference,
+    /// Maximum range of wind direction variability in degrees.
     pub wind_var_maximum: i64,
-    pub wind_maximum: i64,
-    pub gust_maximum: i64,
+    /// Maximum mean wind speed.
+    pub wind_maximum: Speed,
+    /// Maximum difference between gusts and mean wind speed.
+    pub gust_maximum: Speed,
+    /// Reports older than this are bad.
     pub age_maximum: TimeDelta,
+    /// Reports older than this are marginal.
     pub age_marginal: TimeDelta,
-    pub visibility_minimum: i64,
-    pub visibility_marginal: i64,
+    /// Visibilities at or below are bad.
+    pub visibility_minimum: Distance,
+    /// Visibilities below are marginal.
+    pub visibility_marginal: Distance,
+    /// Runway visual ranges at or below are bad.
+    pub rvr_minimum: Distance,
+    /// Runway visual ranges below are marginal.
+    pub rvr_marginal: Distance,
+    /// Units to display all values in. None displays values as reported.
+    pub units: Option<Units>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             position: Position::GeoIP,
-            cloud_minimum: 6,
-            cloud_marginal: 15,
-            temp_minimum: 0,
-            spread_minimum: 3,
+            cloud_minimum: Altitude::new(600, AltitudeUnit::Ft),
+            cloud_marginal: Altitude::new(1500, AltitudeUnit::Ft),
+            temp_minimum: Temperature::new(0, TemperatureUnit::C),
+            spread_minimum: TemperatureDifference::new(3, TemperatureUnit::C),
             wind_var_maximum: 45,
-            wind_maximum: 15,
-            gust_maximum: 10,
+            wind_maximum: Speed::new(15, SpeedUnit::Kt),
+            gust_maximum: Speed::new(10, SpeedUnit::Kt),
             age_maximum: TimeDelta::hours(6),
             age_marginal: TimeDelta::hours(1),
-            visibility_minimum: 1500,
-            visibility_marginal: 5000,
+            visibility_minimum: Distance::new(1500, DistanceUnit::M),
+            visibility_marginal: Distance::new(5000, DistanceUnit::M),
+            rvr_minimum: Distance::new(550, DistanceUnit::M),
+            rvr_marginal: Distance::new(1500, DistanceUnit::M),
+            units: None,
         }
     }
 }
 
-impl Config {
-    pub async fn get_config(secrets: &Secrets, args: &Args) -> Config {
-        let mut config: Config = read_config_file(args.config_file.clone());
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+/// Display units for single quantities, e.g. as given on the command line. Quantities without a unit keep the configured one.
+pub struct UnitOverrides {
+    /// Unit for wind speeds.
+    pub wind_speed: Option<SpeedUnit>,
+    /// Unit for visibilities and runway visual ranges.
+    pub distance: Option<DistanceUnit>,
+    /// Unit for the altimeter setting.
+    pub pressure: Option<PressureUnit>,
+    /// Unit for temperatures and dewpoints.
+    pub temperature: Option<TemperatureUnit>,
+    /// Unit for cloud bases and other altitudes.
+    pub altitude: Option<AltitudeUnit>,
+}
 
-        if let Some(icao) = args.airfield.clone() {
-            config.position = Position::Airfield(icao.clone());
-        } else if let Some(lat) = args.latitude {
-            if let Some(long) = args.longitude {
-                config.position = Position::LatLong(LatLong(lat, long));
-            }
-            println!("Please provide both Latitude and Longitude. Defaulting to geoip...");
+impl Config {
+    /// Overrides the display units with the given ones. Units not given are taken from the config file, or the defaults.
+    pub fn override_units(&mut self, overrides: UnitOverrides) {
+        if overrides == UnitOverrides::default() {
+            return;
+        }
+        let mut units = self.units.unwrap_or_default();
+        if let Some(unit) = overrides.wind_speed {
+            units.wind_speed = unit;
+        }
+        if let Some(unit) = overrides.distance {
+            units.distance = unit;
+        }
+        if let Some(unit) = overrides.pressure {
+            units.pressure = unit;
+        }
+        if let Some(unit) = overrides.temperature {
+            units.temperature = unit;
         }
+        if let Some(unit) = overrides.altitude {
+            units.altitude = unit;
+        }
+        self.units = Some(units);
+    }
 
-        if let Position::Airfield(ref icao) = config.position {
-            if !check_icao_code(icao, secrets).await {
-                println!("Invalid airfield {icao}. Defaulting to geoip...");
-                config.position = Position::GeoIP;
-            }
+    /// Checks an airfield position with `AvWx` and falls back to geoip if the airfield is unknown.
+    pub async fn validate_position(&mut self, secrets: &Secrets) {
+        if let Position::Airfield(ref icao) = self.position
+            && !check_icao_code(icao, secrets).await
+        {
+            println!("Invalid airfield {icao}. Defaulting to geoip...");
+            self.position = Position::GeoIP;
         }
-        config
     }
 }
 
-fn read_config_file(config_filepath: Option<String>) -> Config {
-    let msg = "Failed to load config.";
-    let mut config = Config::default();
-    let config_filepath = config_filepath
-        .unwrap_or(std::env::var("HOME").expect(msg) + "/.config/wxfetch/config.toml");
-    let config_file = File::open(config_filepath.clone());
-    if config_file.is_err() {
-        return config;
-    }
-    let mut config_file = config_file.unwrap();
+/// Reads the configuration from the given TOML file or the default location, falling back to default values if the file does not exist. Does not validate the position, so no network access is needed.
+///
+/// # Errors
+///
+/// Returns an error if no path is given and `HOME` is not set, or if the file exists but cannot be read or is not valid TOML.
+pub fn read_config_file(config_filepath: Option<String>) -> anyhow::Result<Config> {
+    let config_filepath = match config_filepath {
+        Some(path) => path,
+        None => {
+            std::env::var("HOME").context("Failed to locate config file, HOME is not set.")?
+                + "/.config/wxfetch/config.toml"
+        }
+    };
+    let Ok(mut config_file) = File::open(&config_filepath) else {
+        return Ok(Config::default());
+    };
     let mut contents = String::new();
-    config_file.read_to_string(&mut contents).expect(msg);
-    let contents = contents.parse::<Table>().expect(msg);
+    config_file
+        .read_to_string(&mut contents)
+        .with_context(|| format!("Failed to read config file {config_filepath}."))?;
+    let contents = contents
+        .parse::<Table>()
+        .with_context(|| format!("Failed to parse config file {config_filepath}."))?;
+    Ok(config_from_table(&contents))
+}
+
+/// Builds the configuration from the sections of a config file. Keys not given keep their default values.
+fn config_from_table(contents: &Table) -> Config {
+    let mut config = Config::default();
 
     if contents.contains_key("position") {
         let position = &contents["position"];
         if let Some(airfield) = position.get("airfield").and_then(Value::as_str) {
             config.position = Position::Airfield(airfield.to_string());
         }
-        if let Some(lat) = position.get("lat").and_then(Value::as_float) {
-            if let Some(lon) = contents["position"].get("lon").and_then(Value::as_float) {
-                config.position = Position::LatLong(LatLong(lat, lon));
-            }
+        if let Some(lat) = position.get("lat").and_then(Value::as_float)
+            && let Some(lon) = contents["position"].get("lon").and_then(Value::as_float)
+        {
+            config.position = Position::LatLong(LatLong(lat, lon));
         }
     }
 
     if contents.contains_key("clouds") {
         let clouds = &contents["clouds"];
-        if let Some(minimum) = clouds.get("cloud_minimum").and_then(Value::as_integer) {
+        let hectofeet =
+            |value: i64| Altitude::checked_new(value.checked_mul(100)?, AltitudeUnit::Ft);
+        if let Some(minimum) = get_quantity(clouds, "cloud_minimum", hectofeet) {
             config.cloud_minimum = minimum;
         }
-        if let Some(marginal) = clouds.get("cloud_marginal").and_then(Value::as_integer) {
+        if let Some(marginal) = get_quantity(clouds, "cloud_marginal", hectofeet) {
             config.cloud_marginal = marginal;
         }
     }
 
     if contents.contains_key("temperature") {
         let temperature = &contents["temperature"];
-        if let Some(minimum) = temperature.get("temp_minimum").and_then(Value::as_integer) {
+        if let Some(minimum) = get_quantity(temperature, "temp_minimum", |value| {
+            Temperature::checked_new(value, TemperatureUnit::C)
+        }) {
             config.temp_minimum = minimum;
         }
-        if let Some(spread_minimum) = temperature
-            .get("spread_minimum")
-            .and_then(Value::as_integer)
-        {
+        if let Some(spread_minimum) = get_quantity(temperature, "spread_minimum", |value| {
+            TemperatureDifference::checked_new(value, TemperatureUnit::C)
+        }) {
             config.spread_minimum = spread_minimum;
         }
     }
 
     if contents.contains_key("wind") {
         let wind = &contents["wind"];
+        let knots = |value| Speed::checked_new(value, SpeedUnit::Kt);
         if let Some(var_maximum) = wind.get("wind_var_maximum").and_then(Value::as_integer) {
             config.wind_var_maximum = var_maximum;
         }
-        if let Some(maximum) = wind.get("wind_maximum").and_then(Value::as_integer) {
+        if let Some(maximum) = get_quantity(wind, "wind_maximum", knots) {
             config.wind_maximum = maximum;
         }
-        if let Some(gust_maximum) = wind.get("gust_maximum").and_then(Value::as_integer) {
+        if let Some(gust_maximum) = get_quantity(wind, "gust_maximum", knots) {
             config.gust_maximum = gust_maximum;
         }
     }
@@ -151,25 +228,90 @@ fn read_config_file(config_filepath: Option<String>) -> Config {
         }
     }
 
+    let metres = |value| Distance::checked_new(value, DistanceUnit::M);
     if contents.contains_key("visibility") {
         let visibility = &contents["visibility"];
-        if let Some(minimum) = visibility
-            .get("visibility_minimum")
-            .and_then(Value::as_integer)
-        {
+        if let Some(minimum) = get_quantity(visibility, "visibility_minimum", metres) {
             config.visibility_minimum = minimum;
         }
-        if let Some(marginal) = visibility
-            .get("visibility_marginal")
-            .and_then(Value::as_integer)
-        {
+        if let Some(marginal) = get_quantity(visibility, "visibility_marginal", metres) {
             config.visibility_marginal = marginal;
         }
     }
 
+    if contents.contains_key("rvr") {
+        let rvr = &contents["rvr"];
+        if let Some(minimum) = get_quantity(rvr, "rvr_minimum", metres) {
+            config.rvr_minimum = minimum;
+        }
+        if let Some(marginal) = get_quantity(rvr, "rvr_marginal", metres) {
+            config.rvr_marginal = marginal;
+        }
+    }
+
+    if let Some(section) = contents.get("units") {
+        config.units = Some(units_from_toml(section));
+    }
+
     config
 }
 
+/// Reads the display units from the units section of the config. Units not given or invalid are the defaults.
+fn units_from_toml(section: &Value) -> Units {
+    let mut units = Units::default();
+    if let Some(unit) = get_unit(section, "wind_speed") {
+        units.wind_speed = unit;
+    }
+    if let Some(unit) = get_unit(section, "distance") {
+        units.distance = unit;
+    }
+    if let Some(unit) = get_unit(section, "pressure") {
+        units.pressure = unit;
+    }
+    if let Some(unit) = get_unit(section, "temperature") {
+        units.temperature = unit;
+    }
+    if let Some(unit) = get_unit(section, "altitude") {
+        units.altitude = unit;
+    }
+    units
+}
+
+/// Reads a unit from the config, e.g. "kt" or "hpa".
+fn get_unit<U: FromStr>(table: &Value, key: &str) -> Option<U> {
+    let value = table.get(key)?;
+    let unit = value.as_str().and_then(|repr| repr.parse().ok());
+    if unit.is_none() {
+        println!("Invalid unit {value} for {key}. Using default...");
+    }
+    unit
+}
+
+/// Reads a quantity from the config, either with unit (e.g. "20 kt", "3 SM") or as a bare number in the unit given by `from_number`, which returns None if the value is out of range.
+fn get_quantity<Q: FromStr>(
+    table: &Value,
+    key: &str,
+    from_number: impl Fn(i64) -> Option<Q>,
+) -> Option<Q> {
+    let value = table.get(key)?;
+    let quantity = match value {
+        Value::Integer(number) => from_number(*number),
+        #[allow(clippy::cast_possible_truncation)]
+        Value::Float(number) if number.fract() == 0.0 => from_number(*number as i64),
+        Value::String(repr) => repr.parse().ok(),
+        _ => {
+            println!(
+                "Invalid value {value} for {key}. Use a whole number or a quantity with unit, e.g. \"1.5 SM\". Using default..."
+            );
+            return None;
+        }
+    };
+    if quantity.is_none() {
+        println!("Invalid value {value} for {key}. Using default...");
+    }
+    quantity
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -177,7 +319,104 @@ mod test {
     #[tokio::test]
     async fn test_read_config_file() {
         let expected = Config::default();
-        let actual = read_config_file(Some("./config.toml".to_string()));
+        let actual = read_config_file(Some("./config.toml".to_string())).unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[tokio::test]
+    async fn test_read_config_file_units() {
+        let path = std::env::temp_dir().join("wxfetch_test_config_units.toml");
+        std::fs::write(&path, "[units]\nwind_speed = \"mph\"\ndistance = \"sm\"\n").unwrap();
+        let actual = read_config_file(Some(path.to_string_lossy().to_string())).unwrap();
+        let expected = Units {
+            wind_speed: SpeedUnit::Mph,
+            distance: DistanceUnit::Mi,
+            ..Units::default()
+        };
+        assert_eq!(actual.units, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_read_config_file_invalid_units() {
+        let path = std::env::temp_dir().join("wxfetch_test_config_invalid_units.toml");
+        std::fs::write(
+            &path,
+            "[units]\nwind_speed = \"knots\"\ndistance = 5\npressure = \"inhg\"\n",
+        )
+        .unwrap();
+        let actual = read_config_file(Some(path.to_string_lossy().to_string())).unwrap();
+        let expected = Units {
+            pressure: PressureUnit::Inhg,
+            ..Units::default()
+        };
+        assert_eq!(actual.units, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_read_config_file_minima_with_units() {
+        let path = std::env::temp_dir().join("wxfetch_test_config_minima.toml");
+        std::fs::write(
+            &path,
+            "[clouds]\ncloud_minimum = 8\ncloud_marginal = \"500 m\"\n\
+             [temperature]\nspread_minimum = \"9 F\"\n\
+             [wind]\nwind_maximum = \"20 mph\"\n\
+             [visibility]\nvisibility_minimum = \"3 SM\"\nvisibility_marginal = \"far\"\n\
+             [rvr]\nrvr_minimum = 600.0\nrvr_marginal = 1.5\n",
+        )
+        .unwrap();
+        let actual = read_config_file(Some(path.to_string_lossy().to_string())).unwrap();
+        assert_eq!(actual.cloud_minimum, Altitude::new(800, AltitudeUnit::Ft));
+        assert_eq!(actual.cloud_marginal, Altitude::new(500, AltitudeUnit::M));
+        assert_eq!(
+            actual.spread_minimum,
+            TemperatureDifference::new(5, TemperatureUnit::C)
+        );
+        assert_eq!(actual.wind_maximum, Speed::new(20, SpeedUnit::Mph));
+        assert_eq!(
+            actual.visibility_minimum,
+            Distance::new(3, DistanceUnit::Mi)
+        );
+        assert_eq!(
+            actual.visibility_marginal,
+            Config::default().visibility_marginal
+        );
+        assert_eq!(actual.rvr_minimum, Distance::new(600, DistanceUnit::M));
+        assert_eq!(actual.rvr_marginal, Config::default().rvr_marginal);
+    }
+
+    #[tokio::test]
+    async fn test_read_config_file_minima_out_of_range() {
+        let path = std::env::temp_dir().join("wxfetch_test_config_out_of_range.toml");
+        std::fs::write(
+            &path,
+            "[clouds]\ncloud_minimum = 9223372036854775807\n\
+             [wind]\nwind_maximum = \"9223372036854775807 kt\"\n\
+             [visibility]\nvisibility_minimum = 1e30\n",
+        )
+        .unwrap();
+        let actual = read_config_file(Some(path.to_string_lossy().to_string())).unwrap();
+        let expected = Config::default();
+        assert_eq!(actual.cloud_minimum, expected.cloud_minimum);
+        assert_eq!(actual.wind_maximum, expected.wind_maximum);
+        assert_eq!(actual.visibility_minimum, expected.visibility_minimum);
+    }
+
+    #[tokio::test]
+    async fn test_override_units() {
+        let mut config = Config::default();
+        config.override_units(UnitOverrides::default());
+        assert_eq!(config.units, None);
+
+        config.override_units(UnitOverrides {
+            pressure: Some(PressureUnit::Inhg),
+            temperature: Some(TemperatureUnit::F),
+            ..UnitOverrides::default()
+        });
+        let expected = Units {
+            pressure: PressureUnit::Inhg,
+            temperature: TemperatureUnit::F,
+            ..Units::default()
+        };
+        assert_eq!(config.units, Some(expected));
+    }
 }
@@ -0,0 +1,280 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - formats.rs
+
+//! Detection and decoding of stored weather reports in the formats of `AvWx`, aviationweather.gov and plain text.
+
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    Config,
+    metar::{Metar, build_regex},
+};
+
+/// A METAR element of the NOAA dataserver XML.
+static XML_METAR_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"(?s)<METAR>.*?</METAR>"));
+/// The raw text element of a METAR in the NOAA dataserver XML.
+static XML_RAW_TEXT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"(?s)<raw_text>(?<content>.*?)</raw_text>"));
+/// The observation time element of a METAR in the NOAA dataserver XML.
+static XML_OBSERVATION_TIME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"(?s)<observation_time>(?<content>.*?)</observation_time>"));
+
+#[derive(PartialEq, Eq, Debug)]
+/// Supported formats for stored weather reports.
+pub enum Format {
+    /// JSON as returned by the `AvWx` API.
+    AvWxJson,
+    /// JSON as returned by the aviationweather.gov data API.
+    AviationWeatherJson,
+    /// XML as returned by the NOAA dataserver.
+    NoaaXml,
+    /// CSV cache files as provided by NOAA.
+    NoaaCsv,
+    /// Plain METAR text, one report per line.
+    Raw,
+}
+
+impl Format {
+    /// Guesses the format of the given contents of a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for JSON that contains no reports of either API, e.g. an error message, so that it is never mistaken for a report.
+    pub fn detect(contents: &str) -> anyhow::Result<Self> {
+        let contents = contents.trim_start();
+        if contents.starts_with('<') {
+            return Ok(Self::NoaaXml);
+        }
+        if let Ok(json) = serde_json::from_str::<Value>(contents) {
+            return if json_items(&json).any(|item| item.get("rawOb").is_some()) {
+                Ok(Self::AviationWeatherJson)
+            } else if json_items(&json)
+                .any(|item| item.get("raw").is_some() || item.get("station").is_some())
+            {
+                Ok(Self::AvWxJson)
+            } else {
+                Err(anyhow!("Invalid weather data: {json}"))
+            };
+        }
+        if contents.lines().any(|line| line.starts_with("raw_text,")) {
+            return Ok(Self::NoaaCsv);
+        }
+        Ok(Self::Raw)
+    }
+}
+
+/// Decodes all METARs contained in the contents of a file, detecting its format automatically. Reports that cannot be decoded are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the contents are JSON without any reports, see [`Format::detect`].
+pub fn metars_from_str(contents: &str, config: &Config) -> anyhow::Result<Vec<Metar>> {
+    metars_from_str_at(contents, config, Utc::now())
+}
+
+/// Like `metars_from_str`, but resolves the dates of plain text reports relative to `reference` instead of the current time, e.g. for archived reports.
+///
+/// # Errors
+///
+/// Returns an error if the contents are JSON without any reports, see [`Format::detect`].
+pub fn metars_from_str_at(
+    contents: &str,
+    config: &Config,
+    reference: DateTime<Utc>,
+) -> anyhow::Result<Vec<Metar>> {
+    let metars = match Format::detect(contents)? {
+        Format::AvWxJson => serde_json::from_str::<Value>(contents)
+            .map(|json| {
+                json_items(&json)
+                    .filter_map(|item| Metar::from_json(item, config))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Format::AviationWeatherJson => metars_from_aviationweather_json(contents),
+        Format::NoaaXml => metars_from_xml(contents),
+        Format::NoaaCsv => metars_from_csv(contents),
+        Format::Raw => contents
+            .lines()
+            .filter_map(|line| Metar::from_raw_at(line.trim(), reference))
+            .collect(),
+    };
+    Ok(metars)
+}
+
+/// Iterates over the reports in a JSON document, which may either be a single object or an array of objects.
+fn json_items(json: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match json.as_array() {
+        Some(items) => Box::new(items.iter()),
+        None => Box::new(std::iter::once(json)),
+    }
+}
+
+/// Decodes a raw report, using the observation time (if known) to resolve the date.
+fn metar_from_raw_at(raw: &str, observation_time: Option<DateTime<Utc>>) -> Option<Metar> {
+    Metar::from_raw_at(raw, observation_time.unwrap_or_else(Utc::now))
+}
+
+fn metars_from_aviationweather_json(contents: &str) -> Vec<Metar> {
+    let Ok(json) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    json_items(&json)
+        .filter_map(|item| {
+            let raw = item.get("rawOb")?.as_str()?;
+            let observation_time = item
+                .get("obsTime")
+                .and_then(Value::as_i64)
+                .and_then(|secs| DateTime::from_timestamp(secs, 0));
+            metar_from_raw_at(raw, observation_time)
+        })
+        .collect()
+}
+
+fn metars_from_xml(contents: &str) -> Vec<Metar> {
+    XML_METAR_REGEX
+        .find_iter(contents)
+        .filter_map(|report| {
+            let raw = xml_element(report.as_str(), &XML_RAW_TEXT_REGEX)?;
+            let observation_time = xml_element(report.as_str(), &XML_OBSERVATION_TIME_REGEX)
+                .and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+                .map(|time| time.to_utc());
+            metar_from_raw_at(&raw, observation_time)
+        })
+        .collect()
+}
+
+/// Returns the text content of the first element matched by `element`, which captures it as "content".
+fn xml_element(xml: &str, element: &Regex) -> Option<String> {
+    Some(element.captures(xml)?["content"].trim().to_string())
+}
+
+fn metars_from_csv(contents: &str) -> Vec<Metar> {
+    let mut lines = contents
+        .lines()
+        .skip_while(|line| !line.starts_with("raw_text,"));
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<&str> = header.split(',').collect();
+    let time_column = columns.iter().position(|c| *c == "observation_time");
+
+    lines
+        .filter_map(|line| {
+            let values: Vec<&str> = line.split(',').collect();
+            let observation_time = time_column
+                .and_then(|i| values.get(i))
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                .map(|time| time.to_utc());
+            metar_from_raw_at(values.first()?, observation_time)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn read_testdata(filename: &str) -> String {
+        fs::read_to_string(format!("tests/testdata/{filename}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detect_avwx() {
+        let contents = read_testdata("eddk-pretty.json");
+        assert_eq!(Format::detect(&contents).unwrap(), Format::AvWxJson);
+    }
+
+    #[tokio::test]
+    async fn test_detect_aviationweather() {
+        let contents = read_testdata("kjfk-aviationweather.json");
+        assert_eq!(
+            Format::detect(&contents).unwrap(),
+            Format::AviationWeatherJson
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detect_xml() {
+        let contents = read_testdata("eddk-dataserver.xml");
+        assert_eq!(Format::detect(&contents).unwrap(), Format::NoaaXml);
+    }
+
+    #[tokio::test]
+    async fn test_detect_csv() {
+        let contents = read_testdata("metars-cache.csv");
+        assert_eq!(Format::detect(&contents).unwrap(), Format::NoaaCsv);
+    }
+
+    #[tokio::test]
+    async fn test_detect_raw() {
+        let contents = read_testdata("edrk-raw.txt");
+        assert_eq!(Format::detect(&contents).unwrap(), Format::Raw);
+    }
+
+    #[tokio::test]
+    async fn test_metars_from_str_counts() {
+        let config = Config::default();
+        for (filename, count) in [
+            ("eddk-pretty.json", 1),
+            ("kjfk-aviationweather.json", 1),
+            ("eddk-dataserver.xml", 1),
+            ("metars-cache.csv", 2),
+            ("edrk-raw.txt", 2),
+        ] {
+            let metars = metars_from_str(&read_testdata(filename), &config).unwrap();
+            assert_eq!(metars.len(), count, "{filename}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metars_from_xml_station() {
+        let metars = metars_from_xml(&read_testdata("eddk-dataserver.xml"));
+        let colourised = metars
+            .into_iter()
+            .next()
+            .unwrap()
+            .colorise(&Config::default());
+        assert!(colourised.contains("EDDK"));
+        assert!(colourised.contains("210550Z"));
+    }
+
+    #[tokio::test]
+    async fn test_xml_element() {
+        let xml = "<METAR><raw_text> EDDK 210550Z </raw_text></METAR>";
+        assert_eq!(
+            xml_element(xml, &XML_RAW_TEXT_REGEX),
+            Some("EDDK 210550Z".to_string())
+        );
+        assert!(xml_element(xml, &XML_OBSERVATION_TIME_REGEX).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metars_from_str_invalid() {
+        let metars = metars_from_str("this is not a weather report", &Config::default()).unwrap();
+        assert!(metars.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_metars_from_str_error_body() {
+        let contents = r#"{"error": "Your API token is invalid"}"#;
+        assert!(Format::detect(contents).is_err());
+        let err = metars_from_str(contents, &Config::default()).unwrap_err();
+        assert!(err.to_string().contains("Your API token is invalid"));
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - lib.rs
+
+//! Decoding and assessment of aviation weather reports, as used by the `wxfetch` command line utility.
+//!
+//! METARs are decoded from raw text with [`Metar::from_raw`], from `AvWx` JSON with [`Metar::from_json`] or from stored files with [`formats::metars_from_str`]. [`request_wx`] fetches the latest METAR for a [`Position`] from `AvWx`, [`request_taf`] the forecast, which is decoded with [`Taf::from_json`]. AIRMETs, SIGMETs and PIREPs are available through [`request_advisories`] and [`request_pireps`], see [`advisory`]. [`request_notams`] fetches the NOTAMs for a station, which are decoded with [`notam::Notam::from_json`]. Every decoded [`WxField`] carries typed quantities (see [`metar::units`]) and can be converted to other units and assessed against the personal minima of a [`Config`].
+//!
+//! ```
+//! use wxfetch::{Assessment, Config, Metar};
+//!
+//! let metar = Metar::from_raw("EDDK 211450Z 27010KT 9999 FEW040 12/05 Q1013").unwrap();
+//! let report = metar.report(&Config::default());
+//! assert_eq!(report.station, "EDDK");
+//! assert_eq!(report.fields[1].assessment, Assessment::Good);
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod advisory;
+pub mod api;
+pub mod config;
+pub mod formats;
+pub mod metar;
+pub mod notam;
+pub mod position;
+pub mod taf;
+
+pub use api::{
+    check_icao_code, get_coordinates, get_station, request_advisories, request_notams,
+    request_pireps, request_taf, request_wx,
+};
+pub use config::{Config, UnitOverrides, read_config_file};
+pub use metar::{Assessment, Metar, WxField};
+pub use position::{LatLong, Position};
+pub use taf::Taf;
+
+/// Credentials for the weather data providers.
+pub struct Secrets {
+    /// API key for `AvWx`, see <https://avwx.rest/>.
+    pub avwx_api_key: String,
+}
@@ -10,22 +10,25 @@
 // limitations under the License.
 // WxFetch - main.rs
 
-use std::fs::File;
-use std::io::BufReader;
+use std::fs;
+use std::io;
 
-use api::request_wx;
-use clap::Parser;
-
-mod metar;
-use metar::Metar;
-
-mod position;
-
-mod api;
-
-mod config;
-use config::Config;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
 use serde_json::Value;
+use wxfetch::advisory::pirep::Pirep;
+use wxfetch::advisory::{Advisory, Briefing, json_reports};
+use wxfetch::api::AVWX_NOTAM_URL;
+use wxfetch::formats::{metars_from_str, metars_from_str_at};
+use wxfetch::metar::MetarReport;
+use wxfetch::notam::notams_from_json;
+use wxfetch::taf::timeline::Timeline;
+use wxfetch::taf::verification::Verification;
+use wxfetch::taf::{TafReport, split_raw_tafs};
+use wxfetch::{
+    Config, LatLong, Metar, Position, Secrets, Taf, UnitOverrides, get_coordinates, get_station,
+    read_config_file, request_advisories, request_notams, request_pireps, request_taf, request_wx,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -33,27 +36,99 @@ use serde_json::Value;
 ///
 /// For more information see README.md or <https://github.com/RRArny/wxfetch>.
 struct Args {
-    #[arg(short, long, value_name = "ICAO code for an Airfield.")]
+    #[arg(short, long, global = true, value_name = "ICAO code for an Airfield.")]
     airfield: Option<String>,
-    #[arg(long = "lat", value_name = "Latitude")]
+    #[arg(long = "lat", global = true, value_name = "Latitude")]
     latitude: Option<f64>,
-    #[arg(long = "lon", value_name = "Longitude")]
+    #[arg(long = "lon", global = true, value_name = "Longitude")]
     longitude: Option<f64>,
-    #[arg(short, long, value_name = "Config File")]
+    #[arg(short, long, global = true, value_name = "Config File")]
     config_file: Option<String>,
-    #[arg(short, long = "file", value_name = "JSON Source File")]
+    #[arg(short, long = "file", value_name = "Source File")]
     file: Option<String>,
-    #[arg(short, long, value_name = "AvWx API key")]
+    #[arg(short, long, global = true, value_name = "AvWx API key")]
     key: Option<String>,
+    #[arg(short, long, value_name = "Raw METAR text, - for stdin")]
+    raw: Option<String>,
+    /// Display wind speeds in this unit.
+    #[arg(long, value_name = "Speed unit", value_parser = ["kt", "kph", "mph", "mps"])]
+    speed_unit: Option<String>,
+    /// Display visibilities in this unit.
+    #[arg(long, value_name = "Distance unit", value_parser = ["m", "km", "sm", "nm"])]
+    distance_unit: Option<String>,
+    /// Display altimeter settings in this unit.
+    #[arg(long, value_name = "Pressure unit", value_parser = ["hpa", "inhg"])]
+    pressure_unit: Option<String>,
+    /// Display temperatures in this unit.
+    #[arg(long, value_name = "Temperature unit", value_parser = ["c", "f"])]
+    temperature_unit: Option<String>,
+    /// Display cloud bases in this unit.
+    #[arg(long, value_name = "Altitude unit", value_parser = ["ft", "m"])]
+    altitude_unit: Option<String>,
+    /// Output format, coloured text or JSON as described in README.md.
+    #[arg(short, long, global = true, value_name = "Output format", value_parser = ["text", "json"], default_value = "text")]
+    output: String,
+    /// Show the terminal aerodrome forecast (TAF) instead of the METAR.
+    #[arg(short, long)]
+    taf: bool,
+    /// Show the TAF as an hour-by-hour timeline with flight categories.
+    #[arg(long)]
+    timeline: bool,
+    /// Verify the TAF against the METARs observed during its validity, read from this file.
+    #[arg(long, value_name = "METAR file")]
+    verify: Option<String>,
+    /// Show the AIRMETs, SIGMETs and PIREPs for the position instead of the METAR.
+    #[arg(long)]
+    advisories: bool,
+    /// Read raw METARs from stdin, one per line.
+    #[arg(value_name = "-", value_parser = ["-"])]
+    stdin: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-struct Secrets {
-    avwx_api_key: String,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the NOTAMs for the airfield, or the nearest reporting station, highlighting runway and aerodrome closures and navaid outages.
+    Notam {
+        /// URL of the NOTAM endpoint, e.g. of a local mock of the `AvWx` API.
+        #[arg(long, value_name = "NOTAM URL", default_value = AVWX_NOTAM_URL)]
+        url: String,
+    },
+}
+
+/// Reads the config file and applies the position and units given on the command line.
+async fn get_config(secrets: &Secrets, args: &Args) -> Config {
+    let mut config: Config =
+        read_config_file(args.config_file.clone()).unwrap_or_else(|err| panic!("{err:#}"));
+
+    if let Some(icao) = args.airfield.clone() {
+        config.position = Position::Airfield(icao.clone());
+    } else if let Some(lat) = args.latitude {
+        if let Some(long) = args.longitude {
+            config.position = Position::LatLong(LatLong(lat, long));
+        }
+        println!("Please provide both Latitude and Longitude. Defaulting to geoip...");
+    }
+
+    config.override_units(unit_overrides(args));
+    config.validate_position(secrets).await;
+    config
+}
+
+fn unit_overrides(args: &Args) -> UnitOverrides {
+    UnitOverrides {
+        wind_speed: args.speed_unit.as_deref().map(Into::into),
+        distance: args.distance_unit.as_deref().map(Into::into),
+        pressure: args.pressure_unit.as_deref().map(Into::into),
+        temperature: args.temperature_unit.as_deref().map(Into::into),
+        altitude: args.altitude_unit.as_deref().map(Into::into),
+    }
 }
 
 fn get_secrets(param: Option<String>) -> Secrets {
     let avwx_api_key =
-        param.unwrap_or(std::env::var("AVWX_API_KEY").expect("Could not load secret key."));
+        param.unwrap_or_else(|| std::env::var("AVWX_API_KEY").expect("Could not load secret key."));
     Secrets { avwx_api_key }
 }
 
@@ -63,50 +138,351 @@ async fn get_weather(config: &Config, secrets: &Secrets) -> Value {
         .expect("Weather request failed. Check the API key for AvWx and your internet connection. Maybe try another position.")
 }
 
-fn get_weather_from_file(filename: String) -> Value {
-    let file = File::open(filename).expect("No such file.");
-    let reader = BufReader::new(file);
-    serde_json::from_reader(reader).expect("Failed to read data from file.")
+async fn get_forecast(config: &Config, secrets: &Secrets) -> Value {
+    request_taf(config, secrets)
+        .await
+        .expect("Forecast request failed. Check the API key for AvWx and your internet connection. Maybe try another position.")
+}
+
+fn get_weather_from_file(filename: String, config: &Config) -> Vec<Metar> {
+    let contents = fs::read_to_string(filename).expect("Failed to read data from file.");
+    metars_from_str(&contents, config).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Splits raw METAR input into one report per non-empty line. If `raw` is "-" the input is read from stdin instead.
+fn get_raw_reports(raw: String) -> Vec<String> {
+    let text = if raw == "-" {
+        io::read_to_string(io::stdin()).expect("Failed to read from stdin.")
+    } else {
+        raw
+    };
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Reads TAFs from a file, either `AvWx` JSON (a single forecast or an array of them) or raw text.
+fn get_forecasts_from_file(filename: String) -> Vec<Taf> {
+    let contents = fs::read_to_string(filename).expect("Failed to read data from file.");
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(Value::Array(values)) => values.iter().filter_map(Taf::from_json).collect(),
+        Ok(value) => Taf::from_json(&value).into_iter().collect(),
+        Err(_) => tafs_from_raw_text(&contents),
+    }
+}
+
+fn tafs_from_raw_text(text: &str) -> Vec<Taf> {
+    split_raw_tafs(text)
+        .iter()
+        .filter_map(|report| {
+            let taf = Taf::from_raw(report);
+            if taf.is_none() {
+                eprintln!("Invalid TAF: {report}");
+            }
+            taf
+        })
+        .collect()
+}
+
+fn metars_from_raw_reports(reports: &[String]) -> Vec<Metar> {
+    reports
+        .iter()
+        .filter_map(|report| {
+            let metar = Metar::from_raw(report);
+            if metar.is_none() {
+                eprintln!("Invalid METAR: {report}");
+            }
+            metar
+        })
+        .collect()
+}
+
+/// Prints the METARs either colourised, one per line, or as a JSON array.
+fn print_metars(metars: Vec<Metar>, config: &Config, output: &str) {
+    if output == "json" {
+        let reports: Vec<MetarReport> = metars.iter().map(|metar| metar.report(config)).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("Failed to serialize weather reports.")
+        );
+        return;
+    }
+    for metar in metars {
+        println!("{}", metar.colorise(config));
+    }
+}
+
+/// Checks every TAF against the METARs in the file and prints where the forecast busted. Dates of plain text METARs are resolved relative to the end of the validity of the TAF.
+fn print_verifications(tafs: &[Taf], filename: &str, config: &Config, output: &str) {
+    let contents = fs::read_to_string(filename).expect("Failed to read data from file.");
+    let verifications: Vec<Verification> = tafs
+        .iter()
+        .map(|taf| {
+            let metars = metars_from_str_at(&contents, config, taf.valid_to)
+                .unwrap_or_else(|err| panic!("{err}"));
+            Verification::new(taf, &metars)
+        })
+        .collect();
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&verifications)
+                .expect("Failed to serialize verifications.")
+        );
+        return;
+    }
+    for verification in verifications {
+        println!("{}", verification.colourise());
+    }
+}
+
+/// Prints the TAFs either colourised, one line per period, or as a JSON array. With `timeline` every TAF is resolved into hourly conditions instead.
+fn print_tafs(tafs: Vec<Taf>, config: &Config, output: &str, timeline: bool) {
+    if timeline {
+        let timelines: Vec<Timeline> = tafs.iter().map(|taf| Timeline::new(taf, config)).collect();
+        if output == "json" {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&timelines).expect("Failed to serialize forecasts.")
+            );
+        } else {
+            for timeline in timelines {
+                println!("{}", timeline.colourise(config));
+            }
+        }
+        return;
+    }
+    if output == "json" {
+        let reports: Vec<TafReport> = tafs.iter().map(|taf| taf.report(config)).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("Failed to serialize forecasts.")
+        );
+        return;
+    }
+    for taf in tafs {
+        println!("{}", taf.colourise(config));
+    }
+}
+
+/// Prints the TAFs, their timelines or their verification, as selected on the command line.
+fn show_tafs(tafs: Vec<Taf>, config: &Config, args: &Args) {
+    match &args.verify {
+        Some(filename) => print_verifications(&tafs, filename, config, &args.output),
+        None => print_tafs(tafs, config, &args.output, args.timeline),
+    }
+}
+
+/// Fetches the AIRMETs, SIGMETs and PIREPs for the position and prints those currently affecting it.
+async fn show_briefing(config: &Config, secrets: &Secrets, output: &str) {
+    let advisories = request_advisories(config, secrets)
+        .await
+        .expect("Advisory request failed. Check the API key for AvWx and your internet connection. Maybe try another position.");
+    let advisories: Vec<Advisory> = json_reports(&advisories)
+        .into_iter()
+        .filter_map(Advisory::from_json)
+        .collect();
+    let pireps = request_pireps(config, secrets)
+        .await
+        .expect("PIREP request failed. Check the API key for AvWx and your internet connection. Maybe try another position.");
+    let pireps: Vec<Pirep> = json_reports(&pireps)
+        .into_iter()
+        .filter_map(Pirep::from_json)
+        .collect();
+    let coordinates = get_coordinates(config, secrets).await;
+    let briefing = Briefing::new(advisories, pireps, coordinates.as_ref(), Utc::now());
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&briefing).expect("Failed to serialize advisories.")
+        );
+        return;
+    }
+    println!("{}", briefing.colourise());
+}
+
+/// Fetches the NOTAMs for the station at the position and prints those in force, closures and outages first.
+async fn show_notams(config: &Config, secrets: &Secrets, url: &str, output: &str) {
+    let station = get_station(config, secrets)
+        .await
+        .expect("No station found for this position. Maybe try another position.");
+    let json = request_notams(&station, secrets, url)
+        .await
+        .expect("NOTAM request failed. Check the API key for AvWx and your internet connection.");
+    let notams = notams_from_json(&json, Utc::now()).unwrap_or_else(|err| panic!("{err}"));
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&notams).expect("Failed to serialize NOTAMs.")
+        );
+        return;
+    }
+    if notams.is_empty() {
+        println!("No NOTAMs for {station}.");
+    }
+    for notam in notams {
+        println!("{}", notam.colourise());
+    }
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+    if let Some(raw) = args.raw.clone().or(args.stdin.clone()) {
+        let mut config =
+            read_config_file(args.config_file.clone()).unwrap_or_else(|err| panic!("{err:#}"));
+        config.override_units(unit_overrides(&args));
+        if args.taf || args.timeline || args.verify.is_some() {
+            let text = get_raw_reports(raw).join("\n");
+            let tafs = tafs_from_raw_text(&text);
+            show_tafs(tafs, &config, &args);
+            return;
+        }
+        let metars = metars_from_raw_reports(&get_raw_reports(raw));
+        print_metars(metars, &config, &args.output);
+        return;
+    }
     let secrets = get_secrets(args.key.clone());
-    let config = Config::get_config(&secrets, &args).await;
-    let json = match args.file {
-        Some(filename) => get_weather_from_file(filename),
-        None => get_weather(&config, &secrets).await,
+    let config = get_config(&secrets, &args).await;
+    if let Some(Command::Notam { url }) = &args.command {
+        show_notams(&config, &secrets, url, &args.output).await;
+        return;
+    }
+    if args.advisories {
+        show_briefing(&config, &secrets, &args.output).await;
+        return;
+    }
+    if args.taf || args.timeline || args.verify.is_some() {
+        let tafs = match args.file.clone() {
+            Some(filename) => get_forecasts_from_file(filename),
+            None => {
+                let json = get_forecast(&config, &secrets).await;
+                vec![Taf::from_json(&json).expect("Invalid forecast data received.")]
+            }
+        };
+        if tafs.is_empty() {
+            eprintln!("No forecasts found.");
+        }
+        show_tafs(tafs, &config, &args);
+        return;
+    }
+    let metars = match args.file {
+        Some(filename) => get_weather_from_file(filename, &config),
+        None => {
+            let json = get_weather(&config, &secrets).await;
+            vec![Metar::from_json(&json, &config).expect("Invalid weather data received.")]
+        }
     };
-    let wx_string = Metar::from_json(&json, &config)
-        .expect("Invalid weather data received.")
-        .colorise(&config);
 
-    println!("{wx_string}");
+    if metars.is_empty() {
+        eprintln!("No weather reports found.");
+    }
+    print_metars(metars, &config, &args.output);
 }
 
 #[cfg(test)]
 mod test {
     use std::fs;
 
+    use wxfetch::metar::units::{PressureUnit, SpeedUnit};
+
     use super::*;
 
     #[tokio::test]
     async fn test_get_weather_from_file() {
         for entry in fs::read_dir("tests/testdata").unwrap() {
             let path = entry.unwrap().path();
-            let result = get_weather_from_file(path.into_os_string().into_string().unwrap());
-            assert!(result.is_object());
+            let result = get_weather_from_file(
+                path.into_os_string().into_string().unwrap(),
+                &Config::default(),
+            );
+            assert!(!result.is_empty());
         }
     }
 
+    #[tokio::test]
+    async fn test_get_raw_reports() {
+        let raw =
+            "EDDK 210550Z 17004KT CAVOK 19/18 Q1013\n\n  EDRK 210550Z 00000KT 9999 12/08 Q1020  \n";
+        let expected = vec![
+            "EDDK 210550Z 17004KT CAVOK 19/18 Q1013".to_string(),
+            "EDRK 210550Z 00000KT 9999 12/08 Q1020".to_string(),
+        ];
+        let actual = get_raw_reports(raw.to_string());
+        assert_eq!(expected, actual);
+    }
+
+    #[tokio::test]
+    async fn test_args_raw() {
+        let args = Args::parse_from(["wxfetch", "--raw", "EDDK 210550Z 17004KT"]);
+        assert_eq!(args.raw, Some("EDDK 210550Z 17004KT".to_string()));
+        let args = Args::parse_from(["wxfetch", "-"]);
+        assert_eq!(args.stdin, Some("-".to_string()));
+        assert!(Args::try_parse_from(["wxfetch", "EDDK"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tafs_from_raw_text() {
+        let text =
+            "TAF EDDK 211100Z 2112/2218 27010KT 9999 SCT030\n  BECMG 2118/2120 VRB03KT\nINVALID";
+        let tafs = tafs_from_raw_text(text);
+        assert_eq!(tafs.len(), 1);
+        assert_eq!(tafs[0].periods.len(), 2);
+        let args = Args::parse_from(["wxfetch", "--taf", "-"]);
+        assert!(args.taf);
+        let args = Args::parse_from(["wxfetch", "--timeline", "-a", "EDDK"]);
+        assert!(args.timeline);
+    }
+
+    #[tokio::test]
+    async fn test_args_advisories() {
+        let args = Args::parse_from(["wxfetch", "--advisories", "-a", "KDEN"]);
+        assert!(args.advisories);
+        assert!(!Args::parse_from(["wxfetch"]).advisories);
+    }
+
+    #[tokio::test]
+    async fn test_args_notam() {
+        let args = Args::parse_from(["wxfetch", "notam", "-a", "EDDK"]);
+        assert_eq!(args.airfield, Some("EDDK".to_string()));
+        let Some(Command::Notam { url }) = args.command else {
+            panic!("Expected the notam command.");
+        };
+        assert_eq!(url, AVWX_NOTAM_URL);
+        let args = Args::parse_from(["wxfetch", "notam", "--url", "http://localhost:8080/notam"]);
+        assert!(
+            matches!(args.command, Some(Command::Notam { url }) if url == "http://localhost:8080/notam")
+        );
+        let args = Args::parse_from(["wxfetch", "-"]);
+        assert!(args.command.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unit_overrides() {
+        let args = Args::parse_from(["wxfetch"]);
+        assert_eq!(unit_overrides(&args), UnitOverrides::default());
+        let args = Args::parse_from(["wxfetch", "--pressure-unit", "inhg", "--speed-unit", "mps"]);
+        let expected = UnitOverrides {
+            pressure: Some(PressureUnit::Inhg),
+            wind_speed: Some(SpeedUnit::Mps),
+            ..UnitOverrides::default()
+        };
+        assert_eq!(unit_overrides(&args), expected);
+    }
+
     #[tokio::test]
     async fn test_get_weather_from_file_metar() {
         for entry in fs::read_dir("tests/testdata").unwrap() {
             let path = entry.unwrap().path();
-            let json = get_weather_from_file(path.into_os_string().into_string().unwrap());
-            let metar = Metar::from_json(&json, &Config::default());
-            assert!(metar.is_some());
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let json: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+                let metar = Metar::from_json(&json, &Config::default());
+                assert!(metar.is_some());
+            }
         }
     }
 }
@@ -10,127 +10,635 @@
 // limitations under the License.
 // WxFetch - metar.rs
 
+//! Decoding of METARs into [`WxField`]s and their assessment against the personal minima of a [`Config`].
+
 use crate::{Config, position::Position};
+use anyhow::anyhow;
 use chrono::DateTime;
+use chrono::Datelike;
 use chrono::FixedOffset;
+use chrono::Months;
+use chrono::TimeDelta;
 use chrono::Utc;
 use colored::{Color, ColoredString, Colorize};
+use regex::Regex;
+use serde::Serialize;
 use serde_json::Value;
+use std::fmt::Display;
 use std::ops::Mul;
 use std::ops::Sub;
-
-mod clouds;
-mod units;
-mod wxcodes;
-use crate::metar::clouds::{Clouds, get_clouds_from_json};
-use crate::metar::units::{PressureUnit, SpeedUnit, TemperatureUnit, Units};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+pub mod clouds;
+pub mod remarks;
+pub mod remarksinfo;
+pub mod runwaystate;
+pub mod rvr;
+pub mod trend;
+pub mod units;
+pub mod visibility;
+pub mod windshear;
+pub mod wxcodes;
+use crate::metar::clouds::{CloudType, Clouds, clouds_from_str, get_clouds_from_json};
+use crate::metar::remarks::{Remark, RemarkSeverity};
+use crate::metar::remarksinfo::RemarksInfo;
+use crate::metar::runwaystate::{
+    BrakingAction, DepositDepth, RunwayDeposit, RunwayExtent, get_runway_state_from_json,
+    runway_state_from_str,
+};
+use crate::metar::rvr::{RvrLimit, RvrTendency, RvrValue, get_rvr_from_json, rvr_from_str};
+use crate::metar::trend::{Trend, TrendReport, is_trend_start, trends_and_remarks};
+use crate::metar::units::{
+    Altitude, AltitudeUnit, Distance, DistanceUnit, Pressure, PressureUnit, Speed, SpeedUnit,
+    Temperature, TemperatureUnit, Units,
+};
+use crate::metar::visibility::{
+    DirectionalVisibility, get_visibility_from_json, join_visibility_groups, visibility_from_str,
+};
+use crate::metar::windshear::{
+    WindShearRunway, get_wind_shear_from_json, join_wind_shear_groups, wind_shear_from_str,
+};
 use crate::metar::wxcodes::{
     WxCode, WxCodeDescription, WxCodeIntensity, WxCodeProximity, get_wxcodes_from_json,
+    recent_wxcode_from_str, wxcode_from_str,
 };
 
+/// ICAO station code like "EDDK".
+static STATION_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^[A-Z][A-Z0-9]{3}$"));
+/// Observation time like "210550Z".
+static TIMESTAMP_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<day>\d{2})(?<hour>\d{2})(?<minute>\d{2})Z$"));
+/// Wind group like "27015G25KT" or "VRB03KT".
+static WIND_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"^(?<direction>\d{3}|VRB)(?<strength>\d{2,3})(G(?<gusts>\d{2,3}))?(?<unit>KT|MPS|KMH)$",
+    )
+});
+/// Variable wind direction like "240V300".
+static WIND_VAR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<low>\d{3})V(?<high>\d{3})$"));
+/// Temperature and dewpoint like "M02/M08".
+static TEMPERATURE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<temp>M?\d{2})/(?<dewpoint>M?\d{2})$"));
+/// Altimeter setting like "Q1013" or "A2992".
+static QNH_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^(?<unit>[QA])(?<qnh>\d{4})$"));
+
+#[derive(Debug, Serialize)]
 /// Represents a METAR report.
 pub struct Metar {
     /// ICAO code of the issuing station.
-    icao_code: String,
+    #[serde(rename = "station")]
+    pub icao_code: String,
     /// Contents of the report.
-    fields: Vec<WxField>,
+    pub fields: Vec<WxField>,
+    /// Trend forecasts (NOSIG, BECMG, TEMPO).
+    pub trends: Vec<Trend>,
+    /// Values decoded from the remarks, if available.
+    pub remarks_info: Option<RemarksInfo>,
+    /// Report type and modifiers (SPECI, AUTO, COR, NIL).
+    pub modifiers: Vec<ReportModifier>,
     /// True, if this METAR was issued by the exact station that was requested, false otherwise.
-    exact_match: bool,
+    pub exact_match: bool,
     // / Units.
     // units: Units,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
 /// Elements of a METAR report.
 pub enum WxField {
     /// Issue time.
     TimeStamp(DateTime<FixedOffset>),
     /// Prevailing winds.
     Wind {
-        direction: i64,
-        strength: i64,
-        gusts: i64,
-        unit: SpeedUnit,
+        /// Direction the wind is blowing from.
+        direction: WindDirection,
+        /// Mean wind speed.
+        strength: Speed,
+        /// Gust speed, if reported.
+        gusts: Option<Speed>,
     },
     /// Provided if wind direction changing.
-    WindVariability { low_dir: i64, hi_dir: i64 },
-    /// Visibility.
-    Visibility(i64),
+    WindVariability {
+        /// Start of the range of directions in degrees, counted clockwise.
+        low_dir: i64,
+        /// End of the range of directions in degrees, counted clockwise.
+        hi_dir: i64,
+    },
+    /// Prevailing visibility, optionally with the minimum visibility and its direction.
+    Visibility {
+        /// Prevailing visibility.
+        distance: Distance,
+        /// Whether the visibility is above or below the reported value (P, M), e.g. "P6SM".
+        limit: RvrLimit,
+        /// Minimum visibility and its direction, if reported.
+        minimum: Option<DirectionalVisibility>,
+    },
+    /// Ceiling and visibility OK: visibility of 10km or more, no cloud below 5000ft or the minimum sector altitude, no CB or TCU and no significant weather.
+    Cavok,
+    /// Runway visual range for a single runway.
+    RunwayVisualRange {
+        /// Runway designator, e.g. "24L".
+        runway: String,
+        /// Runway visual range, or its minimum if variable.
+        visibility: RvrValue,
+        /// Maximum runway visual range if variable (V).
+        variable: Option<RvrValue>,
+        /// Tendency during the last ten minutes.
+        tendency: RvrTendency,
+        /// Unit of the reported values, metres or feet.
+        unit: DistanceUnit,
+    },
+    /// State of a runway: deposit, its extent and depth, and braking action.
+    RunwayState {
+        /// Runway designator, e.g. "24L".
+        runway: String,
+        /// Type of deposit.
+        deposit: RunwayDeposit,
+        /// Share of the runway covered by the deposit.
+        extent: RunwayExtent,
+        /// Depth of the deposit.
+        depth: DepositDepth,
+        /// Braking action or friction coefficient.
+        braking: BrakingAction,
+    },
     /// Temperature and dewpoint.
     Temperature {
-        temp: i64,
-        dewpoint: i64,
-        unit: TemperatureUnit,
+        /// Air temperature.
+        temp: Temperature,
+        /// Dewpoint temperature.
+        dewpoint: Temperature,
     },
     /// Altimeter setting.
-    Qnh(i64, PressureUnit),
-    /// Observed cloud layers. Altitude in FL (flight level).
-    Clouds(Clouds, i64),
+    Qnh(Pressure),
+    /// Observed cloud layers. Altitude of the cloud base, None if not given or not observable.
+    #[serde(serialize_with = "serialize_clouds")]
+    Clouds(Clouds, Option<Altitude>, CloudType),
     /// Prevailing weather conditions.
-    WxCode(WxCode, WxCodeIntensity, WxCodeProximity, WxCodeDescription),
-    /// Various remarks.
-    Remarks(String),
+    #[serde(serialize_with = "serialize_wx_code")]
+    WxCode(
+        Vec<WxCode>,
+        WxCodeIntensity,
+        WxCodeProximity,
+        WxCodeDescription,
+    ),
+    /// No significant weather (NSW), used in trends to announce the end of significant weather.
+    Nsw,
+    /// Significant weather observed since the previous report but not at the time of observation (RE). The codes are empty if only a descriptor was given (RETS).
+    #[serde(serialize_with = "serialize_recent_weather")]
+    RecentWeather(Vec<WxCode>, WxCodeDescription),
+    /// Wind shear reported in the take-off or approach path.
+    WindShear(WindShearRunway),
+    /// Decoded remarks section.
+    Remarks(Vec<Remark>),
+}
+
+/// Serialized form of a cloud layer, e.g. `{"cover": "bkn", "base": {"value": 800, "unit": "ft"}, "cloud_type": "cb"}`.
+#[derive(Serialize)]
+struct CloudsRepr {
+    cover: Clouds,
+    base: Option<Altitude>,
+    cloud_type: CloudType,
+}
+
+// The signature is given by serde's serialize_with for tuple variants.
+#[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+fn serialize_clouds<S: serde::Serializer>(
+    cover: &Clouds,
+    base: &Option<Altitude>,
+    cloud_type: &CloudType,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    CloudsRepr {
+        cover: *cover,
+        base: *base,
+        cloud_type: *cloud_type,
+    }
+    .serialize(serializer)
+}
+
+/// Serialized form of a weather group, e.g. `{"codes": ["ra"], "intensity": "light", "proximity": "on_station", "descriptor": "sh"}`.
+#[derive(Serialize)]
+struct WxCodeRepr<'a> {
+    codes: &'a [WxCode],
+    intensity: WxCodeIntensity,
+    proximity: WxCodeProximity,
+    descriptor: WxCodeDescription,
+}
+
+// The signature is given by serde's serialize_with for tuple variants.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_wx_code<S: serde::Serializer>(
+    codes: &[WxCode],
+    intensity: &WxCodeIntensity,
+    proximity: &WxCodeProximity,
+    descriptor: &WxCodeDescription,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    WxCodeRepr {
+        codes,
+        intensity: *intensity,
+        proximity: *proximity,
+        descriptor: *descriptor,
+    }
+    .serialize(serializer)
+}
+
+/// Serialized form of a recent weather group, e.g. `{"codes": ["ra"], "descriptor": "ts"}`.
+#[derive(Serialize)]
+struct RecentWeatherRepr<'a> {
+    codes: &'a [WxCode],
+    descriptor: WxCodeDescription,
 }
 
+// The signature is given by serde's serialize_with for tuple variants.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_recent_weather<S: serde::Serializer>(
+    codes: &[WxCode],
+    descriptor: &WxCodeDescription,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    RecentWeatherRepr {
+        codes,
+        descriptor: *descriptor,
+    }
+    .serialize(serializer)
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Direction of the prevailing wind.
+pub enum WindDirection {
+    /// True heading in degrees.
+    Heading(i64),
+    /// Variable direction (VRB).
+    Variable,
+    /// No wind (00000KT).
+    Calm,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Modifiers describing how a report was issued.
+pub enum ReportModifier {
+    /// Special report, issued out of schedule because conditions changed significantly (SPECI).
+    Speci,
+    /// Fully automated report (AUTO).
+    Auto,
+    /// Corrected report (COR).
+    Cor,
+    /// Missing report (NIL).
+    Nil,
+    /// Amended forecast (AMD), TAF only.
+    Amd,
+    /// Cancelled forecast (CNL), TAF only.
+    Cnl,
+}
+
+impl FromStr for ReportModifier {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "speci" => Ok(Self::Speci),
+            "auto" => Ok(Self::Auto),
+            "cor" => Ok(Self::Cor),
+            "nil" => Ok(Self::Nil),
+            "amd" => Ok(Self::Amd),
+            "cnl" => Ok(Self::Cnl),
+            _ => Err(anyhow!("Invalid report modifier {s}.")),
+        }
+    }
+}
+
+impl Display for ReportModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            ReportModifier::Speci => "SPECI",
+            ReportModifier::Auto => "AUTO",
+            ReportModifier::Cor => "COR",
+            ReportModifier::Nil => "NIL",
+            ReportModifier::Amd => "AMD",
+            ReportModifier::Cnl => "CNL",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl ReportModifier {
+    /// The modifier, highlighted if it calls for attention.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let repr = self.to_string();
+        match self {
+            ReportModifier::Speci | ReportModifier::Amd => repr.bold().black().on_yellow(),
+            ReportModifier::Cor => repr.bold().bright_white().on_magenta(),
+            ReportModifier::Nil | ReportModifier::Cnl => repr.bold().bright_white().on_red(),
+            ReportModifier::Auto => repr.white(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Rating of a field against the personal minima, ordered from best to worst.
+pub enum Assessment {
+    /// Within the minima, rendered green.
+    Good,
+    /// Close to the minima, rendered yellow.
+    Marginal,
+    /// Outside the minima, rendered red.
+    Bad,
+}
+
+impl From<Color> for Assessment {
+    fn from(colour: Color) -> Self {
+        match colour {
+            Color::Red | Color::BrightRed => Assessment::Bad,
+            Color::Yellow | Color::BrightYellow => Assessment::Marginal,
+            _ => Assessment::Good,
+        }
+    }
+}
+
+impl From<Assessment> for Color {
+    fn from(assessment: Assessment) -> Self {
+        match assessment {
+            Assessment::Good => Color::Green,
+            Assessment::Marginal => Color::Yellow,
+            Assessment::Bad => Color::Red,
+        }
+    }
+}
+
+impl From<RemarkSeverity> for Assessment {
+    fn from(severity: RemarkSeverity) -> Self {
+        match severity {
+            RemarkSeverity::Routine => Assessment::Good,
+            RemarkSeverity::Caution => Assessment::Marginal,
+            RemarkSeverity::Warning => Assessment::Bad,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+/// A field in the display units of the config, along with its assessment.
+pub struct AssessedField {
+    /// The field, converted to the display units.
+    #[serde(flatten)]
+    pub field: WxField,
+    /// Its assessment against the personal minima.
+    pub assessment: Assessment,
+}
+
+impl AssessedField {
+    /// Converts the field to the display units of the config and assesses it.
+    #[must_use]
+    pub fn new(field: &WxField, config: &Config) -> Self {
+        AssessedField {
+            field: config
+                .units
+                .map_or_else(|| field.clone(), |units| field.in_units(&units)),
+            assessment: field.assess(config),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// Decoded METAR with all fields assessed against the personal minima. This is the format of the JSON output, see README.md.
+pub struct MetarReport {
+    /// ICAO code of the issuing station.
+    pub station: String,
+    /// True, if this METAR was issued by the exact station that was requested, false otherwise.
+    pub exact_match: bool,
+    /// Report type and modifiers (SPECI, AUTO, COR, NIL).
+    pub modifiers: Vec<ReportModifier>,
+    /// Worst assessment of all observed fields, not considering trends.
+    pub assessment: Assessment,
+    /// Observed conditions.
+    pub fields: Vec<AssessedField>,
+    /// Trend forecasts (NOSIG, BECMG, TEMPO).
+    pub trends: Vec<TrendReport>,
+    /// Values decoded from the remarks by `AvWx`, if available.
+    pub remarks_info: Option<RemarksInfo>,
+}
+
+/// Below this strength ICAO reports variable winds for small changes in direction. At or above it, VRB means the direction varies by 180° or more.
+const LIGHT_VARIABLE_WIND: i64 = 3;
+
+/// Standard pressure in hPa, below which the altimeter setting is highlighted.
+const STANDARD_PRESSURE: i64 = 1013;
+
 impl WxField {
-    pub fn colourise(&self, config: &Config) -> ColoredString {
+    /// Converts all physical quantities of the field to the given units. Runway visual range stays in the unit it was reported in.
+    #[must_use]
+    pub fn in_units(&self, units: &Units) -> WxField {
         match self {
-            WxField::Visibility(vis) => colourise_visibility(*vis, config),
-            WxField::TimeStamp(datetime) => colourize_timestamp(datetime, config),
+            WxField::Visibility {
+                distance,
+                limit,
+                minimum,
+            } => WxField::Visibility {
+                distance: distance.convert(units.distance),
+                limit: *limit,
+                minimum: minimum.map(|minimum| DirectionalVisibility {
+                    distance: minimum.distance.convert(units.distance),
+                    direction: minimum.direction,
+                }),
+            },
             WxField::Wind {
                 direction,
                 strength,
                 gusts,
+            } => WxField::Wind {
+                direction: *direction,
+                strength: strength.convert(units.wind_speed),
+                gusts: gusts.map(|gusts| gusts.convert(units.wind_speed)),
+            },
+            WxField::Temperature { temp, dewpoint } => WxField::Temperature {
+                temp: temp.convert(units.temperature),
+                dewpoint: dewpoint.convert(units.temperature),
+            },
+            WxField::Qnh(qnh) => WxField::Qnh(qnh.convert(units.pressure)),
+            WxField::Clouds(cloud, alt, cloud_type) => WxField::Clouds(
+                *cloud,
+                alt.map(|alt| alt.convert(units.altitude)),
+                *cloud_type,
+            ),
+            field => field.clone(),
+        }
+    }
+
+    /// Rates the field against the personal minima. Composite fields are rated by their worst part, e.g. wind by the worse of strength and gusts.
+    #[must_use]
+    pub fn assess(&self, config: &Config) -> Assessment {
+        match self {
+            WxField::TimeStamp(datetime) => {
+                age_colour(Utc::now().sub(datetime.to_utc()), config).into()
+            }
+            WxField::Wind {
+                direction,
+                strength,
+                gusts,
+            } => {
+                let mut assessment = Assessment::from(wind_colour(*strength, config));
+                if let Some(gusts) = gusts {
+                    assessment = assessment.max(gust_colour(*strength, *gusts, config).into());
+                }
+                if *direction == WindDirection::Variable {
+                    assessment = assessment.max(variable_wind_colour(*strength).into());
+                }
+                assessment
+            }
+            WxField::WindVariability { low_dir, hi_dir } => {
+                wind_var_colour(*low_dir, *hi_dir, config).into()
+            }
+            WxField::Visibility {
+                distance, minimum, ..
+            } => minimum.map_or(visibility_colour(distance, config).into(), |minimum| {
+                Assessment::from(visibility_colour(distance, config))
+                    .max(visibility_colour(&minimum.distance, config).into())
+            }),
+            WxField::Cavok | WxField::Nsw => Assessment::Good,
+            WxField::RunwayVisualRange {
+                visibility,
+                variable,
+                unit,
+                ..
+            } => rvr_colour(visibility, variable.as_ref(), *unit, config).into(),
+            WxField::RunwayState {
+                deposit,
+                depth,
+                braking,
+                ..
+            } => runway_state_colour(*deposit, *depth, *braking).into(),
+            WxField::Temperature { temp, dewpoint } => {
+                Assessment::from(temperature_colour(*temp, config))
+                    .max(spread_colour(*temp, *dewpoint, config).into())
+            }
+            WxField::Qnh(qnh) => qnh_colour(*qnh).into(),
+            WxField::Clouds(cloud, alt, cloud_type) => {
+                Assessment::from(cloud_type_colour(*cloud_type))
+                    .max(cloud_base_colour(*cloud, *alt, config).into())
+            }
+            WxField::WxCode(codes, intensity, _, descriptor) => {
+                assess_wx_code(codes, *intensity, *descriptor)
+            }
+            WxField::RecentWeather(codes, descriptor) => {
+                if is_severe_recent_weather(codes, *descriptor) {
+                    Assessment::Bad
+                } else {
+                    Assessment::Marginal
+                }
+            }
+            WxField::WindShear(_) => Assessment::Bad,
+            WxField::Remarks(remarks) => remarks
+                .iter()
+                .map(|remark| Assessment::from(remark.severity))
+                .max()
+                .unwrap_or(Assessment::Good),
+        }
+    }
+
+    /// Colourises the field according to the config, after converting it to the display units selected there.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let field = config
+            .units
+            .map_or_else(|| self.clone(), |units| self.in_units(&units));
+        match &field {
+            WxField::Visibility {
+                distance,
+                limit,
+                minimum,
+            } => colourise_visibility(distance, *limit, minimum.as_ref(), config),
+            WxField::Cavok => "CAVOK".bright_green(),
+            WxField::RunwayVisualRange {
+                runway,
+                visibility,
+                variable,
+                tendency,
                 unit,
-            } => colourise_wind(*direction, *strength, *gusts, *unit, config),
+            } => colourise_rvr(
+                runway,
+                visibility,
+                variable.as_ref(),
+                *tendency,
+                *unit,
+                config,
+            ),
+            WxField::RunwayState {
+                runway,
+                deposit,
+                extent,
+                depth,
+                braking,
+            } => colourise_runway_state(runway, *deposit, *extent, *depth, *braking, config),
+            WxField::TimeStamp(datetime) => colourize_timestamp(datetime, config),
+            WxField::Wind {
+                direction,
+                strength,
+                gusts,
+            } => colourise_wind(*direction, *strength, *gusts, config),
             WxField::WindVariability { low_dir, hi_dir } => {
                 colourise_wind_var(*low_dir, *hi_dir, config)
             }
-            WxField::Temperature {
-                temp,
-                dewpoint,
-                unit,
-            } => colourise_temperature(*temp, *dewpoint, *unit, config),
-            WxField::Qnh(qnh, unit) => colourise_qnh(*qnh, *unit, config),
-            WxField::WxCode(code, intensity, proximity, descriptor) => {
-                colourise_wx_code(code, intensity, proximity, descriptor, config)
+            WxField::Temperature { temp, dewpoint } => {
+                colourise_temperature(*temp, *dewpoint, config)
+            }
+            WxField::Qnh(qnh) => colourise_qnh(*qnh, config),
+            WxField::WxCode(codes, intensity, proximity, descriptor) => {
+                colourise_wx_code(codes, *intensity, *proximity, *descriptor, config)
+            }
+            WxField::Nsw => "NSW".green(),
+            WxField::RecentWeather(codes, descriptor) => {
+                colourise_recent_weather(codes, *descriptor, config)
+            }
+            WxField::WindShear(runway) => format!("WS {runway}").bold().bright_white().on_red(),
+            WxField::Remarks(remarks) => colourise_remarks(remarks, config),
+            WxField::Clouds(cloud, alt, cloud_type) => {
+                colourise_clouds(*cloud, *alt, *cloud_type, config)
             }
-            WxField::Remarks(str) => str.black().on_white(),
-            WxField::Clouds(cloud, alt) => colourise_clouds(cloud, *alt, config),
         }
     }
 }
 
-fn colourise_clouds(cloud: &Clouds, alt: i64, config: &Config) -> ColoredString {
-    let res: ColoredString = format!("{cloud}").color(match cloud {
-        Clouds::Ovc => Color::Red,
-        Clouds::Brk => Color::Yellow,
-        _ => Color::Green,
-    });
-    let altstr: ColoredString = format!("{alt}").color(if alt <= config.cloud_minimum {
-        Color::Red
-    } else if alt <= config.cloud_marginal {
-        Color::Yellow
-    } else {
-        Color::Green
-    });
-    format!("{res}{altstr}").into()
+fn colourise_clouds(
+    cloud: Clouds,
+    alt: Option<Altitude>,
+    cloud_type: CloudType,
+    config: &Config,
+) -> ColoredString {
+    let res: ColoredString = format!("{cloud}").color(cloud_cover_colour(cloud, alt, config));
+    let base_colour = cloud_base_colour(cloud, alt, config);
+    let altstr: ColoredString = match alt {
+        Some(alt) => format_cloud_altitude(alt).color(base_colour),
+        None if matches!(cloud, Clouds::Vv) => "///".color(base_colour),
+        None => "".into(),
+    };
+    let typestr: ColoredString = match cloud_type_colour(cloud_type) {
+        Color::Red => format!("{cloud_type}").bold().bright_white().on_red(),
+        Color::Yellow => format!("{cloud_type}").bold().black().on_yellow(),
+        _ => format!("{cloud_type}").white(),
+    };
+    format!("{res}{altstr}{typestr}").into()
 }
 
 fn colourise_wx_code(
-    code: &WxCode,
-    intensity: &WxCodeIntensity,
-    proximity: &WxCodeProximity,
-    descriptor: &WxCodeDescription,
+    codes: &[WxCode],
+    intensity: WxCodeIntensity,
+    proximity: WxCodeProximity,
+    descriptor: WxCodeDescription,
     _config: &Config,
 ) -> ColoredString {
-    let codestr = format!("{code}").color(match code {
-        WxCode::Ra => Color::BrightYellow,
-        WxCode::Gr | WxCode::Sn | WxCode::Up => Color::Red,
-        WxCode::Gs => Color::Yellow,
-        WxCode::Po => Color::BrightRed,
-        _ => Color::White,
-    });
+    let mut codestr = String::new();
+    for code in codes {
+        codestr = format!(
+            "{codestr}{}",
+            format!("{code}").color(wx_code_colour(*code))
+        );
+    }
 
     let intensitystr = format!("{intensity}").color(match intensity {
         WxCodeIntensity::Light => Color::BrightGreen,
@@ -147,110 +655,411 @@ fn colourise_wx_code(
 
     let proxstr = format!("{proximity}").white();
 
-    format!("{intensitystr}{descrstr}{codestr}{proxstr}").into()
+    format!("{intensitystr}{proxstr}{descrstr}{codestr}").into()
 }
 
-fn colourise_qnh(qnh: i64, unit: PressureUnit, _config: &Config) -> ColoredString {
-    match unit {
-        PressureUnit::Hpa => format!("Q{qnh}").color(if qnh >= 1013 {
-            Color::Green
-        } else {
-            Color::Yellow
-        }),
-        PressureUnit::Inhg => format!("A{}", qnh / 100).color(if qnh >= 2992 {
-            Color::Green
-        } else {
-            Color::Yellow
-        }),
+/// Rates a single weather phenomenon: hail, snow, ice pellets, unknown precipitation and hazards such as funnel clouds, volcanic ash, squalls, dust and sand storms or whirls are bad. Other precipitation and obscurations like fog and mist are marginal, haze and spray good.
+fn wx_code_assessment(code: WxCode) -> Assessment {
+    match code {
+        WxCode::Gr
+        | WxCode::Sn
+        | WxCode::Pl
+        | WxCode::Up
+        | WxCode::Fc
+        | WxCode::Va
+        | WxCode::Sq
+        | WxCode::Ds
+        | WxCode::Ss
+        | WxCode::Po => Assessment::Bad,
+        WxCode::Ra
+        | WxCode::Dz
+        | WxCode::Gs
+        | WxCode::Ic
+        | WxCode::Sg
+        | WxCode::Fg
+        | WxCode::Br
+        | WxCode::Du
+        | WxCode::Sa
+        | WxCode::Fu => Assessment::Marginal,
+        WxCode::Hz | WxCode::Py => Assessment::Good,
+    }
+}
+
+/// Colours a weather phenomenon by its assessment. Harmless phenomena are shown in white rather than green, as they are still worth noting.
+fn wx_code_colour(code: WxCode) -> Color {
+    match wx_code_assessment(code) {
+        Assessment::Bad => Color::Red,
+        Assessment::Marginal => Color::Yellow,
+        Assessment::Good => Color::White,
+    }
+}
+
+/// Rates present weather: thunderstorms, freezing precipitation, heavy intensity and hazardous phenomena are bad, showers and other precipitation marginal.
+fn assess_wx_code(
+    codes: &[WxCode],
+    intensity: WxCodeIntensity,
+    descriptor: WxCodeDescription,
+) -> Assessment {
+    let descriptor_assessment = match descriptor {
+        WxCodeDescription::Ts | WxCodeDescription::Fz => Assessment::Bad,
+        WxCodeDescription::Sh => Assessment::Marginal,
+        _ => Assessment::Good,
+    };
+    let intensity_assessment = match intensity {
+        WxCodeIntensity::Heavy => Assessment::Bad,
+        _ => Assessment::Good,
+    };
+    codes.iter().map(|code| wx_code_assessment(*code)).fold(
+        descriptor_assessment.max(intensity_assessment),
+        Assessment::max,
+    )
+}
+
+fn colourise_recent_weather(
+    codes: &[WxCode],
+    descriptor: WxCodeDescription,
+    _config: &Config,
+) -> ColoredString {
+    let codestr: String = codes.iter().map(ToString::to_string).collect();
+    let repr = format!("RE{descriptor}{codestr}").bold();
+    if is_severe_recent_weather(codes, descriptor) {
+        repr.bright_white().on_red()
+    } else {
+        repr.black().on_yellow()
+    }
+}
+
+/// True if recent weather includes thunderstorms, hail or funnel clouds.
+fn is_severe_recent_weather(codes: &[WxCode], descriptor: WxCodeDescription) -> bool {
+    descriptor == WxCodeDescription::Ts
+        || codes
+            .iter()
+            .any(|code| matches!(code, WxCode::Gr | WxCode::Fc))
+}
+
+/// Rates the cover of a cloud layer. Layers forming a ceiling (BKN, OVC, VV) are rated by their base, all others are good.
+fn cloud_cover_colour(cloud: Clouds, alt: Option<Altitude>, config: &Config) -> Color {
+    match cloud {
+        Clouds::Bkn | Clouds::Ovc | Clouds::Vv => cloud_base_colour(cloud, alt, config),
+        _ => Color::Green,
+    }
+}
+
+/// Rates the base of a cloud layer against the minima. An unknown vertical visibility (VV///) is bad, other unknown bases are good.
+fn cloud_base_colour(cloud: Clouds, alt: Option<Altitude>, config: &Config) -> Color {
+    match alt {
+        Some(alt) => cloud_altitude_colour(alt, config),
+        None if cloud == Clouds::Vv => Color::Red,
+        None => Color::Green,
+    }
+}
+
+/// Rates a convective cloud type: CB is bad, TCU marginal.
+fn cloud_type_colour(cloud_type: CloudType) -> Color {
+    match cloud_type {
+        CloudType::Cb => Color::Red,
+        CloudType::Tcu => Color::Yellow,
+        CloudType::Unknown | CloudType::None => Color::Green,
+    }
+}
+
+/// Rates a cloud base against the minima.
+fn cloud_altitude_colour(alt: Altitude, config: &Config) -> Color {
+    if alt <= config.cloud_minimum {
+        Color::Red
+    } else if alt <= config.cloud_marginal {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+/// Formats a cloud base the way it is given in a METAR, as three digits in hundreds of feet. Metres are given in full with unit.
+fn format_cloud_altitude(alt: Altitude) -> String {
+    match alt.unit {
+        AltitudeUnit::Ft => format!("{:03}", alt.value() / 100),
+        AltitudeUnit::M => format!("{}M", alt.value()),
+    }
+}
+
+fn colourise_qnh(qnh: Pressure, _config: &Config) -> ColoredString {
+    let prefix = match qnh.unit {
+        PressureUnit::Hpa => "Q",
+        PressureUnit::Inhg => "A",
+    };
+    format!("{prefix}{:04}", qnh.value()).color(qnh_colour(qnh))
+}
+
+fn qnh_colour(qnh: Pressure) -> Color {
+    if qnh.convert(PressureUnit::Hpa).value() >= STANDARD_PRESSURE {
+        Color::Green
+    } else {
+        Color::Yellow
+    }
+}
+
+/// Formats a temperature the way it is given in a METAR: two digits, M for negative values.
+fn format_temperature(temp: Temperature) -> String {
+    let value = temp.value();
+    let sign = if value < 0 { "M" } else { "" };
+    match temp.unit {
+        TemperatureUnit::C => format!("{sign}{:02}", value.abs()),
+        TemperatureUnit::F => format!("{sign}{:02}F", value.abs()),
     }
 }
 
 fn colourise_temperature(
-    temp: i64,
-    dewpoint: i64,
-    _unit: TemperatureUnit,
+    temp: Temperature,
+    dewpoint: Temperature,
     config: &Config,
 ) -> ColoredString {
-    let temp_str = temp.to_string().color(if temp > config.temp_minimum {
+    let temp_str = format_temperature(temp).color(temperature_colour(temp, config));
+    let dew_str = format_temperature(dewpoint).color(spread_colour(temp, dewpoint, config));
+    format!("{temp_str}/{dew_str}").into()
+}
+
+fn temperature_colour(temp: Temperature, config: &Config) -> Color {
+    if temp > config.temp_minimum {
         Color::BrightGreen
     } else {
         Color::BrightRed
-    });
-    let dew_str = dewpoint
-        .to_string()
-        .color(if temp - dewpoint > config.spread_minimum {
-            Color::Green
-        } else {
-            Color::Red
-        });
-    format!("{temp_str}/{dew_str}").into()
+    }
+}
+
+/// Rates the spread between temperature and dewpoint, a small spread indicating risk of fog.
+fn spread_colour(temp: Temperature, dewpoint: Temperature, config: &Config) -> Color {
+    if temp.spread(&dewpoint) > config.spread_minimum {
+        Color::Green
+    } else {
+        Color::Red
+    }
 }
 
 fn colourise_wind_var(low_dir: i64, hi_dir: i64, config: &Config) -> ColoredString {
-    format!("{low_dir}V{hi_dir}").color(if hi_dir - low_dir < config.wind_var_maximum {
+    format!("{low_dir}V{hi_dir}").color(wind_var_colour(low_dir, hi_dir, config))
+}
+
+fn wind_var_colour(low_dir: i64, hi_dir: i64, config: &Config) -> Color {
+    if hi_dir - low_dir < config.wind_var_maximum {
         Color::Green
     } else {
         Color::Yellow
-    })
+    }
 }
 
 fn colourise_wind(
-    direction: i64,
-    strength: i64,
-    gusts: i64,
-    _unit: SpeedUnit,
+    direction: WindDirection,
+    strength: Speed,
+    gusts: Option<Speed>,
     config: &Config,
 ) -> ColoredString {
-    let dir_str = format!("{direction:03}").to_string();
-    let strength_str =
-        format!("{strength:02}")
-            .to_string()
-            .color(if strength > config.wind_maximum {
-                Color::Red
-            } else {
-                Color::Green
-            });
+    let dir_str: ColoredString = match direction {
+        WindDirection::Heading(heading) => format!("{heading:03}").into(),
+        WindDirection::Variable => "VRB".color(variable_wind_colour(strength)),
+        WindDirection::Calm => return "CALM".green(),
+    };
+    let strength_str = format!("{:02}", strength.value()).color(wind_colour(strength, config));
     let mut output: ColoredString = format!("{dir_str}{strength_str}").into();
-    if gusts > 0 {
-        let gust_str =
-            format!("{gusts:02}")
-                .to_string()
-                .color(if gusts - strength > config.gust_maximum {
-                    Color::BrightRed
-                } else {
-                    Color::Green
-                });
+    if let Some(gusts) = gusts {
+        let gust_str = format!("{:02}", gusts.value()).color(gust_colour(strength, gusts, config));
         output = format!("{output}G{gust_str}").into();
     }
-    output = format!("{output}KT").into();
+    output = format!("{output}{}", strength.unit).into();
     output
 }
 
+/// Variable winds are only unremarkable while they are light.
+fn variable_wind_colour(strength: Speed) -> Color {
+    if strength < Speed::new(LIGHT_VARIABLE_WIND, SpeedUnit::Kt) {
+        Color::Green
+    } else {
+        Color::Yellow
+    }
+}
+
+fn wind_colour(strength: Speed, config: &Config) -> Color {
+    if strength > config.wind_maximum {
+        Color::Red
+    } else {
+        Color::Green
+    }
+}
+
+fn gust_colour(strength: Speed, gusts: Speed, config: &Config) -> Color {
+    if gusts - strength > config.gust_maximum {
+        Color::BrightRed
+    } else {
+        Color::Green
+    }
+}
+
 fn colourize_timestamp(datetime: &DateTime<FixedOffset>, config: &Config) -> ColoredString {
     let now: DateTime<Utc> = Utc::now();
     let utctime = datetime.to_utc();
     let dt = now.sub(utctime);
     let str_rep: String = utctime.format("%d%H%MZ").to_string();
-    str_rep.color(if dt.lt(&config.age_marginal) {
+    str_rep.color(age_colour(dt, config))
+}
+
+fn age_colour(age: TimeDelta, config: &Config) -> Color {
+    if age.lt(&config.age_marginal) {
         Color::Green
-    } else if dt.lt(&config.age_maximum) {
+    } else if age.lt(&config.age_maximum) {
         Color::Yellow
     } else {
         Color::Red
-    })
+    }
 }
 
-fn colourise_visibility(vis: i64, config: &Config) -> ColoredString {
-    format!("{vis:04}").color(if vis >= config.visibility_marginal {
+fn colourise_visibility(
+    distance: &Distance,
+    limit: RvrLimit,
+    minimum: Option<&DirectionalVisibility>,
+    config: &Config,
+) -> ColoredString {
+    let prevailing = format!("{limit}{}", format_visibility_distance(distance))
+        .color(visibility_colour(distance, config));
+    match minimum {
+        Some(minimum) => {
+            let minimum_str = format!(
+                "{}{}",
+                format_visibility_distance(&minimum.distance),
+                minimum.direction
+            )
+            .color(visibility_colour(&minimum.distance, config));
+            format!("{prevailing} {minimum_str}").into()
+        }
+        None => prevailing,
+    }
+}
+
+/// Formats a visibility the way it is given in a METAR: four digits for metres, otherwise with unit.
+fn format_visibility_distance(distance: &Distance) -> String {
+    match distance.unit {
+        DistanceUnit::M => format!("{:04}", distance.value()),
+        DistanceUnit::Mi => format!("{distance}SM"),
+        DistanceUnit::Km => format!("{distance}KM"),
+        DistanceUnit::Nm => format!("{distance}NM"),
+        DistanceUnit::Ft => format!("{distance}FT"),
+    }
+}
+
+/// Rates a visibility against the minima.
+fn visibility_colour(distance: &Distance, config: &Config) -> Color {
+    if *distance >= config.visibility_marginal {
         Color::Green
-    } else if vis > config.visibility_minimum {
+    } else if *distance > config.visibility_minimum {
         Color::Yellow
     } else {
         Color::Red
-    })
+    }
+}
+
+fn colourise_rvr(
+    runway: &str,
+    visibility: &RvrValue,
+    variable: Option<&RvrValue>,
+    tendency: RvrTendency,
+    unit: DistanceUnit,
+    config: &Config,
+) -> ColoredString {
+    let mut range = visibility.to_string();
+    if let Some(var) = variable {
+        range = format!("{range}V{var}");
+    }
+    if unit == DistanceUnit::Ft {
+        range.push_str("FT");
+    }
+    let range_str = range.color(rvr_colour(visibility, variable, unit, config));
+    let tendency_str = match tendency {
+        RvrTendency::None => String::new(),
+        _ => format!("/{tendency}"),
+    };
+    format!("R{runway}/{range_str}{tendency_str}").into()
+}
+
+/// Rates the lowest reported runway visual range against the minima.
+fn rvr_colour(
+    visibility: &RvrValue,
+    variable: Option<&RvrValue>,
+    unit: DistanceUnit,
+    config: &Config,
+) -> Color {
+    let lowest = variable.map_or(visibility.range, |var| var.range.min(visibility.range));
+    let lowest = Distance::new(lowest, unit);
+    if lowest >= config.rvr_marginal {
+        Color::Green
+    } else if lowest > config.rvr_minimum {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+fn colourise_remarks(remarks: &[Remark], _config: &Config) -> ColoredString {
+    let mut output: ColoredString = "RMK".black().on_white();
+    for remark in remarks {
+        output = format!("{output} {}", remark.colourise()).into();
+    }
+    output
+}
+
+fn colourise_runway_state(
+    runway: &str,
+    deposit: RunwayDeposit,
+    extent: RunwayExtent,
+    depth: DepositDepth,
+    braking: BrakingAction,
+    _config: &Config,
+) -> ColoredString {
+    let description: Vec<String> = [
+        deposit.to_string(),
+        extent.to_string(),
+        depth.to_string(),
+        braking.to_string(),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect();
+    format!("R{runway}: {}", description.join(", "))
+        .color(runway_state_colour(deposit, depth, braking))
+}
+
+/// Rates a runway state by the worse of its deposit and its braking action.
+fn runway_state_colour(
+    deposit: RunwayDeposit,
+    depth: DepositDepth,
+    braking: BrakingAction,
+) -> Color {
+    let braking_colour = match braking.estimate() {
+        BrakingAction::Poor => Color::Red,
+        BrakingAction::MediumPoor | BrakingAction::Medium | BrakingAction::Unreliable => {
+            Color::Yellow
+        }
+        _ => Color::Green,
+    };
+    let deposit_colour = match deposit {
+        _ if depth == DepositDepth::NotOperational => Color::Red,
+        RunwayDeposit::Slush | RunwayDeposit::Ice | RunwayDeposit::FrozenRuts => Color::Red,
+        RunwayDeposit::Wet
+        | RunwayDeposit::Frost
+        | RunwayDeposit::DrySnow
+        | RunwayDeposit::WetSnow
+        | RunwayDeposit::CompactedSnow => Color::Yellow,
+        _ => Color::Green,
+    };
+    if braking_colour == Color::Red || deposit_colour == Color::Red {
+        Color::Red
+    } else if braking_colour == Color::Yellow || deposit_colour == Color::Yellow {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
 }
 
 impl Metar {
+    /// Parses a METAR as returned by `AvWx`. Fields that are missing or cannot be decoded are skipped.
+    #[must_use]
     pub fn from_json(json: &Value, config: &Config) -> Option<Self> {
         let mut station = String::new();
         if let Some(icao) = json.get("station") {
@@ -273,10 +1082,13 @@ impl Metar {
             fields.push(wind_var);
         }
 
-        if let Some(vis) = get_visibility(json, units) {
+        if let Some(vis) = get_visibility_from_json(json, units) {
             fields.push(vis);
         }
 
+        fields.append(&mut get_rvr_from_json(json));
+        fields.append(&mut get_runway_state_from_json(json));
+
         if let Some(temp) = get_temp(json, units) {
             fields.push(temp);
         }
@@ -286,10 +1098,12 @@ impl Metar {
         }
 
         fields.append(&mut get_wxcodes_from_json(json));
+        fields.append(&mut get_wind_shear_from_json(json));
 
         fields.append(&mut get_clouds_from_json(json));
 
-        if let Some(rmks) = get_remarks(json) {
+        let (trends, remarks) = get_trends_and_remarks(json);
+        if let Some(rmks) = remarks {
             fields.push(rmks);
         }
 
@@ -298,10 +1112,99 @@ impl Metar {
         Some(Metar {
             icao_code: station,
             fields,
+            trends,
+            remarks_info: RemarksInfo::from_json(json),
+            modifiers: get_modifiers_from_json(json),
             exact_match,
         })
     }
 
+    /// Parses a METAR from its raw ICAO/WMO textual representation, e.g. "EDDK 210550Z 17004KT CAVOK 19/18 Q1013 NOSIG". Groups that cannot be decoded are skipped, everything from the RMK section onwards is kept as remarks. Returns None if no valid station code is found.
+    #[must_use]
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        Self::from_raw_at(raw, Utc::now())
+    }
+
+    /// Like `from_raw`, but resolves the day of month of the issue time relative to `reference` instead of the current time. Useful for archived reports.
+    #[must_use]
+    pub fn from_raw_at(raw: &str, reference: DateTime<Utc>) -> Option<Self> {
+        let mut tokens = raw
+            .split_whitespace()
+            .map(|token| token.trim_end_matches('='))
+            .filter(|token| !token.is_empty());
+
+        let mut modifiers: Vec<ReportModifier> = Vec::new();
+        let mut station = tokens.next()?;
+        while matches!(station, "METAR" | "SPECI" | "COR") {
+            if let Ok(modifier) = station.parse() {
+                modifiers.push(modifier);
+            }
+            station = tokens.next()?;
+        }
+        if !is_station_code(station) {
+            return None;
+        }
+
+        let mut fields: Vec<WxField> = Vec::new();
+        let mut supplementary: Vec<&str> = Vec::new();
+
+        let groups = join_visibility_groups(join_wind_shear_groups(tokens));
+        for token in groups.iter().map(String::as_str) {
+            if !supplementary.is_empty() || is_supplementary_start(token) {
+                supplementary.push(token);
+            } else if let Ok(modifier) = token.parse() {
+                modifiers.push(modifier);
+            } else if let Some(time) = timestamp_from_str(token, reference) {
+                fields.push(time);
+            } else if let Some(field) = field_from_str(token) {
+                fields.push(field);
+            }
+        }
+
+        let (trends, remarks) = trends_and_remarks(&supplementary);
+        if let Some(rmks) = remarks {
+            fields.push(rmks);
+        }
+
+        Some(Metar {
+            icao_code: station.to_string(),
+            fields,
+            trends,
+            remarks_info: None,
+            modifiers,
+            exact_match: true,
+        })
+    }
+
+    /// Assesses all fields against the personal minima and converts them to the display units, for JSON output.
+    #[must_use]
+    pub fn report(&self, config: &Config) -> MetarReport {
+        let fields: Vec<AssessedField> = self
+            .fields
+            .iter()
+            .map(|field| AssessedField::new(field, config))
+            .collect();
+        MetarReport {
+            station: self.icao_code.clone(),
+            exact_match: self.exact_match,
+            modifiers: self.modifiers.clone(),
+            assessment: fields
+                .iter()
+                .map(|field| field.assessment)
+                .max()
+                .unwrap_or(Assessment::Good),
+            fields,
+            trends: self
+                .trends
+                .iter()
+                .map(|trend| trend.report(config))
+                .collect(),
+            remarks_info: self.remarks_info.clone(),
+        }
+    }
+
+    /// The decoded METAR, with each field coloured by its assessment against the personal minima.
+    #[must_use]
     pub fn colorise(self, config: &Config) -> ColoredString {
         let mut coloured_string: ColoredString = if self.exact_match {
             self.icao_code.bright_white().on_blue()
@@ -309,17 +1212,65 @@ impl Metar {
             self.icao_code.black().on_yellow()
         };
 
-        for field in self.fields {
+        for modifier in &self.modifiers {
+            coloured_string = format!("{} {}", coloured_string, modifier.colourise()).into();
+        }
+
+        let (remarks, fields): (Vec<WxField>, Vec<WxField>) = self
+            .fields
+            .into_iter()
+            .partition(|field| matches!(field, WxField::Remarks(_)));
+
+        for field in fields {
             coloured_string = format!("{} {}", coloured_string, field.colourise(config)).into();
         }
 
+        for trend in self.trends {
+            coloured_string = format!("{} {}", coloured_string, trend.colourise(config)).into();
+        }
+
+        for field in remarks {
+            coloured_string = format!("{} {}", coloured_string, field.colourise(config)).into();
+        }
+
+        if let Some(info) = self.remarks_info {
+            coloured_string = format!("{}\n    {}", coloured_string, info.colourise()).into();
+        }
+
         coloured_string
     }
 }
 
-fn get_remarks(json: &Value) -> Option<WxField> {
-    let rmks = json.get("remarks")?.as_str()?.to_string();
-    Some(WxField::Remarks(rmks))
+impl FromStr for Metar {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_raw(s).ok_or_else(|| anyhow!("Invalid METAR {s}."))
+    }
+}
+
+/// `AvWx` does not decode the report modifiers, so they are read from the raw report.
+fn get_modifiers_from_json(json: &Value) -> Vec<ReportModifier> {
+    json.get("raw")
+        .and_then(Value::as_str)
+        .map(|raw| {
+            raw.split_whitespace()
+                .take_while(|token| !is_supplementary_start(token))
+                .filter_map(|token| token.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `AvWx` includes the trend forecast in the remarks, so they are split up again here.
+fn get_trends_and_remarks(json: &Value) -> (Vec<Trend>, Option<WxField>) {
+    match json.get("remarks").and_then(Value::as_str) {
+        Some(rmks) => {
+            let tokens: Vec<&str> = rmks.split_whitespace().collect();
+            trends_and_remarks(&tokens)
+        }
+        None => (Vec::new(), None),
+    }
 }
 
 fn get_timestamp(json: &Value) -> Option<WxField> {
@@ -337,16 +1288,15 @@ fn get_qnh(json: &Value, units: Units) -> Option<WxField> {
         qnh_val.as_i64()?
     };
 
-    Some(WxField::Qnh(qnh, units.pressure))
+    Some(WxField::Qnh(Pressure::new(qnh, units.pressure)))
 }
 
 fn get_temp(json: &Value, units: Units) -> Option<WxField> {
     let temp = json.get("temperature")?.get("value")?.as_i64()?;
     let dewpoint = json.get("dewpoint")?.get("value")?.as_i64()?;
     Some(WxField::Temperature {
-        temp,
-        dewpoint,
-        unit: units.temperature,
+        temp: Temperature::new(temp, units.temperature),
+        dewpoint: Temperature::new(dewpoint, units.temperature),
     })
 }
 
@@ -366,25 +1316,135 @@ fn get_wind_var(json: &Value) -> Option<WxField> {
 }
 
 fn get_winds(json: &Value, units: Units) -> Option<WxField> {
-    let direction = json.get("wind_direction")?.get("value")?.as_i64()?;
+    let wind_direction = json.get("wind_direction")?;
     let strength = json.get("wind_speed")?.get("value")?.as_i64()?;
+    let direction = if strength == 0 {
+        WindDirection::Calm
+    } else if wind_direction.get("repr").and_then(Value::as_str) == Some("VRB") {
+        WindDirection::Variable
+    } else {
+        WindDirection::Heading(wind_direction.get("value")?.as_i64()?)
+    };
     let gusts = json
         .get("wind_gust")
         .and_then(|g| g.get("value"))
         .and_then(serde_json::Value::as_i64)
-        .unwrap_or(0);
+        .map(|gusts| Speed::new(gusts, units.wind_speed));
 
     Some(WxField::Wind {
         direction,
-        strength,
+        strength: Speed::new(strength, units.wind_speed),
         gusts,
-        unit: units.wind_speed,
     })
 }
 
-fn get_visibility(json: &Value, _units: Units) -> Option<WxField> {
-    let vis = json.get("visibility")?.get("value")?.as_i64()?;
-    Some(WxField::Visibility(vis))
+/// Parses a single group of a raw METAR into the matching `WxField`, if any.
+pub(crate) fn field_from_str(repr: &str) -> Option<WxField> {
+    wind_from_str(repr)
+        .or_else(|| wind_var_from_str(repr))
+        .or_else(|| visibility_from_str(repr))
+        .or_else(|| rvr_from_str(repr))
+        .or_else(|| runway_state_from_str(repr))
+        .or_else(|| temp_from_str(repr))
+        .or_else(|| qnh_from_str(repr))
+        .or_else(|| wxcode_from_str(repr))
+        .or_else(|| recent_wxcode_from_str(repr))
+        .or_else(|| wind_shear_from_str(repr))
+        .or_else(|| clouds_from_str(repr))
+}
+
+/// Creates a `Regex` from a pattern known to be valid.
+pub(crate) fn build_regex(pattern: &str) -> Regex {
+    Regex::new(pattern)
+        .expect("Creating RegEx pattern failed. This is likely a software bug, please report it.")
+}
+
+pub(crate) fn is_station_code(repr: &str) -> bool {
+    STATION_REGEX.is_match(repr)
+}
+
+/// True if `repr` starts the trend or remarks section following the observation.
+fn is_supplementary_start(repr: &str) -> bool {
+    repr == "RMK" || is_trend_start(repr)
+}
+
+/// Parses a "ddhhmmZ" group. As the group only contains the day of the month, the timestamp is placed in the most recent month (relative to `now`) for which it is not in the future.
+fn timestamp_from_str(repr: &str, now: DateTime<Utc>) -> Option<WxField> {
+    let matches = TIMESTAMP_REGEX.captures(repr)?;
+    let day: u32 = matches["day"].parse().ok()?;
+    let hour: u32 = matches["hour"].parse().ok()?;
+    let minute: u32 = matches["minute"].parse().ok()?;
+
+    let this_month = now
+        .date_naive()
+        .with_day(day)
+        .and_then(|date| date.and_hms_opt(hour, minute, 0))
+        .map(|datetime| datetime.and_utc())
+        .filter(|datetime| *datetime <= now);
+    let datetime = match this_month {
+        Some(datetime) => datetime,
+        None => now
+            .date_naive()
+            .with_day(1)?
+            .checked_sub_months(Months::new(1))?
+            .with_day(day)?
+            .and_hms_opt(hour, minute, 0)?
+            .and_utc(),
+    };
+    Some(WxField::TimeStamp(datetime.fixed_offset()))
+}
+
+fn wind_from_str(repr: &str) -> Option<WxField> {
+    let matches = WIND_REGEX.captures(repr)?;
+    let strength: i64 = matches["strength"].parse().ok()?;
+    let direction = match &matches["direction"] {
+        _ if strength == 0 => WindDirection::Calm,
+        "VRB" => WindDirection::Variable,
+        heading => WindDirection::Heading(heading.parse().ok()?),
+    };
+    let unit = SpeedUnit::from(&matches["unit"]);
+    let gusts = matches
+        .name("gusts")
+        .and_then(|g| g.as_str().parse().ok())
+        .map(|gusts| Speed::new(gusts, unit));
+    Some(WxField::Wind {
+        direction,
+        strength: Speed::new(strength, unit),
+        gusts,
+    })
+}
+
+fn wind_var_from_str(repr: &str) -> Option<WxField> {
+    let matches = WIND_VAR_REGEX.captures(repr)?;
+    Some(WxField::WindVariability {
+        low_dir: matches["low"].parse().ok()?,
+        hi_dir: matches["high"].parse().ok()?,
+    })
+}
+
+fn temp_from_str(repr: &str) -> Option<WxField> {
+    let matches = TEMPERATURE_REGEX.captures(repr)?;
+    let parse_temp = |s: &str| -> Option<Temperature> {
+        Some(Temperature::new(
+            s.replace('M', "-").parse().ok()?,
+            TemperatureUnit::C,
+        ))
+    };
+    Some(WxField::Temperature {
+        temp: parse_temp(&matches["temp"])?,
+        dewpoint: parse_temp(&matches["dewpoint"])?,
+    })
+}
+
+fn qnh_from_str(repr: &str) -> Option<WxField> {
+    let matches = QNH_REGEX.captures(repr)?;
+    let qnh: i64 = matches["qnh"].parse().ok()?;
+    let unit = if &matches["unit"] == "A" {
+        PressureUnit::Inhg
+    } else {
+        PressureUnit::Hpa
+    };
+    Some(WxField::Qnh(Pressure::new(qnh, unit)))
 }
 
 fn is_exact_match(station: &str, config: &Config) -> bool {
@@ -402,6 +1462,7 @@ mod tests {
 
     use crate::position::LatLong;
 
+    use super::remarks::remarks_from_tokens;
     use super::*;
 
     #[tokio::test]
@@ -463,24 +1524,223 @@ mod tests {
         assert!(is_exact_match("EDRK", &config));
     }
 
+    #[tokio::test]
+    async fn test_metar_from_raw() {
+        let metar =
+            Metar::from_raw("EDDK 210550Z AUTO 17004KT 130V190 CAVOK 19/18 Q1013 NOSIG").unwrap();
+        let expected = vec![
+            WxField::Wind {
+                direction: WindDirection::Heading(170),
+                strength: Speed::new(4, SpeedUnit::Kt),
+                gusts: None,
+            },
+            WxField::WindVariability {
+                low_dir: 130,
+                hi_dir: 190,
+            },
+            WxField::Cavok,
+            WxField::Temperature {
+                temp: Temperature::new(19, TemperatureUnit::C),
+                dewpoint: Temperature::new(18, TemperatureUnit::C),
+            },
+            WxField::Qnh(Pressure::new(1013, PressureUnit::Hpa)),
+        ];
+        assert_eq!(metar.icao_code, "EDDK");
+        assert!(metar.exact_match);
+        assert!(matches!(metar.fields[0], WxField::TimeStamp(_)));
+        assert_eq!(metar.fields[1..], expected);
+        assert_eq!(metar.trends.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_trend() {
+        let metar = Metar::from_raw(
+            "EDDK 210550Z 17004KT 9999 FEW030 19/18 Q1013 BECMG FM0630 24012KT TEMPO 0800 FG RMK TEST",
+        )
+        .unwrap();
+        assert_eq!(metar.trends.len(), 2);
+        assert!(metar.trends[1].fields.contains(&WxField::Visibility {
+            distance: Distance::new(800, DistanceUnit::M),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        }));
+        assert_eq!(
+            metar.fields.last(),
+            Some(&WxField::Remarks(remarks_from_tokens(&["RMK", "TEST"])))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_json_trend() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/nearest-pretty.json").unwrap(),
+        )
+        .unwrap();
+        let metar = Metar::from_json(&json, &Config::default()).unwrap();
+        assert_eq!(metar.trends.len(), 1);
+        assert!(
+            metar
+                .fields
+                .contains(&WxField::Remarks(remarks_from_tokens(&[
+                    "FEW150", "BKN250"
+                ])))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_colorise_trend_before_remarks() {
+        let metar = Metar::from_raw("EDDK 17004KT NOSIG RMK TEST").unwrap();
+        let actual = metar.colorise(&Config::default()).clear().to_string();
+        assert!(actual.ends_with("NOSIG RMK TEST"));
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_matches_json() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/edfh_pretty.json").unwrap(),
+        )
+        .unwrap();
+        let from_json = Metar::from_json(&json, &Config::default()).unwrap();
+        let from_raw = Metar::from_raw(json["raw"].as_str().unwrap()).unwrap();
+        assert_eq!(from_json.icao_code, from_raw.icao_code);
+        for field in from_raw.fields {
+            assert!(matches!(field, WxField::TimeStamp(_)) || from_json.fields.contains(&field));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_us() {
+        let metar = Metar::from_raw(
+            "METAR KJFK 031551Z 35021G29KT 10SM -RA FEW024 OVC046 M01/M07 A2966 RMK AO2 SLP042=",
+        )
+        .unwrap();
+        assert_eq!(metar.icao_code, "KJFK");
+        assert!(metar.fields.contains(&WxField::Wind {
+            direction: WindDirection::Heading(350),
+            strength: Speed::new(21, SpeedUnit::Kt),
+            gusts: Some(Speed::new(29, SpeedUnit::Kt)),
+        }));
+        assert!(metar.fields.contains(&WxField::Visibility {
+            distance: Distance::new(10, DistanceUnit::Mi),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        }));
+        assert!(metar.fields.contains(&WxField::WxCode(
+            vec![WxCode::Ra],
+            WxCodeIntensity::Light,
+            WxCodeProximity::OnStation,
+            WxCodeDescription::None,
+        )));
+        assert!(metar.fields.contains(&WxField::Clouds(
+            Clouds::Few,
+            Some(Altitude::new(2400, AltitudeUnit::Ft)),
+            CloudType::None
+        )));
+        assert!(metar.fields.contains(&WxField::Clouds(
+            Clouds::Ovc,
+            Some(Altitude::new(4600, AltitudeUnit::Ft)),
+            CloudType::None
+        )));
+        assert!(metar.fields.contains(&WxField::Temperature {
+            temp: Temperature::new(-1, TemperatureUnit::C),
+            dewpoint: Temperature::new(-7, TemperatureUnit::C),
+        }));
+        assert!(
+            metar
+                .fields
+                .contains(&WxField::Qnh(Pressure::new(2966, PressureUnit::Inhg)))
+        );
+        assert!(
+            metar
+                .fields
+                .contains(&WxField::Remarks(remarks_from_tokens(&[
+                    "RMK", "AO2", "SLP042"
+                ])))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_invalid() {
+        assert!(Metar::from_raw("").is_none());
+        assert!(Metar::from_raw("17004KT CAVOK").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_str() {
+        assert!(Metar::from_str("EDRK 210550Z 00000KT 9999 SCT050 12/08 Q1020").is_ok());
+        assert!(Metar::from_str("not a metar").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_from_str() {
+        let now = DateTime::parse_from_rfc3339("2024-06-21T08:00:00Z")
+            .unwrap()
+            .to_utc();
+        let expected = DateTime::parse_from_rfc3339("2024-06-21T05:50:00Z").unwrap();
+        let actual = timestamp_from_str("210550Z", now);
+        assert_eq!(actual, Some(WxField::TimeStamp(expected)));
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_from_str_previous_month() {
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:10:00Z")
+            .unwrap()
+            .to_utc();
+        let expected = DateTime::parse_from_rfc3339("2024-02-29T23:50:00Z").unwrap();
+        let actual = timestamp_from_str("292350Z", now);
+        assert_eq!(actual, Some(WxField::TimeStamp(expected)));
+    }
+
+    #[tokio::test]
+    async fn test_wind_from_str_mps() {
+        let expected = WxField::Wind {
+            direction: WindDirection::Heading(240),
+            strength: Speed::new(7, SpeedUnit::Mps),
+            gusts: Some(Speed::new(12, SpeedUnit::Mps)),
+        };
+        assert_eq!(wind_from_str("24007G12MPS"), Some(expected));
+        assert!(wind_from_str("2407KT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_field_from_str_unknown() {
+        assert!(field_from_str("AUTO").is_none());
+        assert!(field_from_str("EGSS").is_none());
+    }
+
     #[tokio::test]
     async fn test_colourise_visibility_good() {
         let config = Config::default();
-        let vis_str: ColoredString = colourise_visibility(9999, &config);
+        let vis_str: ColoredString = colourise_visibility(
+            &Distance::new(9999, DistanceUnit::M),
+            RvrLimit::Exact,
+            None,
+            &config,
+        );
         assert_eq!(vis_str.fgcolor, Some(Color::Green));
     }
 
     #[tokio::test]
     async fn test_colourise_visibility_medium() {
         let config = Config::default();
-        let vis_str: ColoredString = colourise_visibility(2000, &config);
+        let vis_str: ColoredString = colourise_visibility(
+            &Distance::new(2000, DistanceUnit::M),
+            RvrLimit::Exact,
+            None,
+            &config,
+        );
         assert_eq!(vis_str.fgcolor, Some(Color::Yellow));
     }
 
     #[tokio::test]
     async fn test_colourise_visibility_bad() {
         let config = Config::default();
-        let vis_str: ColoredString = colourise_visibility(1000, &config);
+        let vis_str: ColoredString = colourise_visibility(
+            &Distance::new(1000, DistanceUnit::M),
+            RvrLimit::Exact,
+            None,
+            &config,
+        );
         assert_eq!(vis_str.fgcolor, Some(Color::Red));
     }
 
@@ -488,10 +1748,9 @@ mod tests {
     async fn test_get_winds() {
         let json: Value = Value::from_str("{\"wind_direction\": {\"value\":100}, \"wind_speed\":{\"value\":10}, \"wind_gust\":{\"value\":15}}").unwrap();
         let expected = WxField::Wind {
-            direction: 100,
-            strength: 10,
-            gusts: 15,
-            unit: SpeedUnit::Kt,
+            direction: WindDirection::Heading(100),
+            strength: Speed::new(10, SpeedUnit::Kt),
+            gusts: Some(Speed::new(15, SpeedUnit::Kt)),
         };
         let actual = get_winds(&json, Units::default());
         assert!(actual.is_some_and(|w| w == expected));
@@ -503,10 +1762,9 @@ mod tests {
             Value::from_str("{\"wind_direction\": {\"value\":100}, \"wind_speed\":{\"value\":10}}")
                 .unwrap();
         let expected = WxField::Wind {
-            direction: 100,
-            strength: 10,
-            gusts: 0,
-            unit: SpeedUnit::Kt,
+            direction: WindDirection::Heading(100),
+            strength: Speed::new(10, SpeedUnit::Kt),
+            gusts: None,
         };
         let actual = get_winds(&json, Units::default());
         assert!(actual.is_some_and(|w| w == expected));
@@ -515,7 +1773,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_qnh() {
         let json: Value = Value::from_str("{\"altimeter\":{\"value\": 1013}}").unwrap();
-        let expected = WxField::Qnh(1013, PressureUnit::Hpa);
+        let expected = WxField::Qnh(Pressure::new(1013, PressureUnit::Hpa));
         let actual = get_qnh(&json, Units::default());
         assert!(actual.is_some_and(|q| q == expected));
     }
@@ -523,7 +1781,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_qnh_inhg() {
         let json: Value = Value::from_str("{\"altimeter\":{\"value\": 29.92}}").unwrap();
-        let expected = WxField::Qnh(2992, PressureUnit::Inhg);
+        let expected = WxField::Qnh(Pressure::new(2992, PressureUnit::Inhg));
         let units = Units {
             pressure: PressureUnit::Inhg,
             altitude: AltitudeUnit::Ft,
@@ -539,8 +1797,8 @@ mod tests {
     #[tokio::test]
     async fn test_get_remarks() {
         let json: Value = Value::from_str("{\"remarks\":\"RWY UNAVAILABLE\"}").unwrap();
-        let expected = "RWY UNAVAILABLE".to_string();
-        let actual = get_remarks(&json);
+        let expected = remarks_from_tokens(&["RWY", "UNAVAILABLE"]);
+        let (_, actual) = get_trends_and_remarks(&json);
         assert!(actual.is_some_and(|r| r == WxField::Remarks(expected)));
     }
 
@@ -550,9 +1808,8 @@ mod tests {
             Value::from_str("{\"temperature\":{\"value\": 10}, \"dewpoint\":{\"value\": 9}}")
                 .unwrap();
         let expected: WxField = WxField::Temperature {
-            temp: 10,
-            dewpoint: 9,
-            unit: TemperatureUnit::C,
+            temp: Temperature::new(10, TemperatureUnit::C),
+            dewpoint: Temperature::new(9, TemperatureUnit::C),
         };
         let actual = get_temp(&json, Units::default());
         assert!(actual.is_some_and(|t| t == expected));
@@ -574,16 +1831,100 @@ mod tests {
     #[tokio::test]
     async fn test_get_visibility() {
         let json: Value = Value::from_str("{\"visibility\":{\"value\":9999}}").unwrap();
-        let expected: WxField = WxField::Visibility(9999);
-        let actual = get_visibility(&json, Units::default());
+        let expected: WxField = WxField::Visibility {
+            distance: Distance::new(9999, DistanceUnit::M),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        };
+        let actual = get_visibility_from_json(&json, Units::default());
         assert!(actual.is_some_and(|v| v == expected));
     }
 
+    #[tokio::test]
+    async fn test_get_visibility_cavok() {
+        let json: Value =
+            Value::from_str("{\"visibility\":{\"repr\":\"CAVOK\",\"value\":9999}}").unwrap();
+        let actual = get_visibility_from_json(&json, Units::default());
+        assert_eq!(actual, Some(WxField::Cavok));
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_json_cavok() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/eddk-pretty.json").unwrap(),
+        )
+        .unwrap();
+        let metar = Metar::from_json(&json, &Config::default()).unwrap();
+        assert!(metar.fields.contains(&WxField::Cavok));
+        assert!(
+            !metar
+                .fields
+                .iter()
+                .any(|f| matches!(f, WxField::Visibility { .. }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_colourise_visibility_statute_miles() {
+        let config = Config::default();
+        let vis = visibility_from_str("1 1/2SM").unwrap().colourise(&config);
+        assert_eq!(vis.fgcolor, Some(Color::Yellow));
+        assert_eq!(vis.clear().to_string(), "1 1/2SM");
+        let vis = visibility_from_str("P6SM").unwrap().colourise(&config);
+        assert_eq!(vis.fgcolor, Some(Color::Green));
+        assert_eq!(vis.clear().to_string(), "P6SM");
+        let vis = visibility_from_str("M1/4SM").unwrap().colourise(&config);
+        assert_eq!(vis.fgcolor, Some(Color::Red));
+    }
+
+    #[tokio::test]
+    async fn test_colourise_visibility_directional() {
+        let config = Config::default();
+        let vis = visibility_from_str("6000 1200NE")
+            .unwrap()
+            .colourise(&config);
+        assert_eq!(vis.clear().to_string(), "6000 1200NE");
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_fractional_visibility() {
+        let metar =
+            Metar::from_raw("KBOS 101254Z 04012KT 1 1/2SM -SN BR OVC008 M01/M02 A2990").unwrap();
+        assert!(metar.fields.contains(&WxField::Visibility {
+            distance: Distance::fraction(3, 2, DistanceUnit::Mi).unwrap(),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_zero_denominator_visibility() {
+        let metar = Metar::from_raw("KJFK 210550Z 17004KT 1 1/0SM OVC010 19/18 A2992").unwrap();
+        assert!(
+            !metar
+                .fields
+                .iter()
+                .any(|field| matches!(field, WxField::Visibility { .. }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_colourise_cavok() {
+        let config = Config::default();
+        let actual = WxField::Cavok.colourise(&config);
+        assert_eq!(actual, "CAVOK".bright_green());
+    }
+
     #[tokio::test]
     async fn test_colourise_vis() {
         let config = Config::default();
-        let vis = WxField::Visibility(9999);
-        let expected = colourise_visibility(9999, &config);
+        let vis = visibility_from_str("9999").unwrap();
+        let expected = colourise_visibility(
+            &Distance::new(9999, DistanceUnit::M),
+            RvrLimit::Exact,
+            None,
+            &config,
+        );
         let actual = vis.colourise(&config);
         assert_eq!(actual, expected);
     }
@@ -592,16 +1933,95 @@ mod tests {
     async fn test_colourise_wind() {
         let config = Config::default();
         let wind = WxField::Wind {
-            direction: 0,
-            strength: 0,
-            gusts: 0,
-            unit: SpeedUnit::Kt,
+            direction: WindDirection::Heading(0),
+            strength: Speed::new(0, SpeedUnit::Kt),
+            gusts: None,
         };
-        let expected = colourise_wind(0, 0, 0, SpeedUnit::Kt, &config);
+        let expected = colourise_wind(
+            WindDirection::Heading(0),
+            Speed::new(0, SpeedUnit::Kt),
+            None,
+            &config,
+        );
         let actual = wind.colourise(&config);
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_wind_calm() {
+        let config = Config::default();
+        let actual = colourise_wind(
+            WindDirection::Calm,
+            Speed::new(0, SpeedUnit::Kt),
+            None,
+            &config,
+        );
+        assert_eq!(actual, "CALM".green());
+    }
+
+    #[tokio::test]
+    async fn test_colourise_wind_variable() {
+        let config = Config::default();
+        let actual = colourise_wind(
+            WindDirection::Variable,
+            Speed::new(3, SpeedUnit::Kt),
+            None,
+            &config,
+        );
+        assert_eq!(actual.clear().to_string(), "VRB03KT");
+    }
+
+    #[tokio::test]
+    async fn test_get_winds_variable() {
+        let json: Value = Value::from_str(
+            "{\"wind_direction\": {\"repr\":\"VRB\",\"value\":null}, \"wind_speed\":{\"value\":3}}",
+        )
+        .unwrap();
+        let expected = WxField::Wind {
+            direction: WindDirection::Variable,
+            strength: Speed::new(3, SpeedUnit::Kt),
+            gusts: None,
+        };
+        assert_eq!(get_winds(&json, Units::default()), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_get_winds_calm() {
+        let json: Value = Value::from_str(
+            "{\"wind_direction\": {\"repr\":\"000\",\"value\":0}, \"wind_speed\":{\"value\":0}}",
+        )
+        .unwrap();
+        let actual = get_winds(&json, Units::default());
+        assert!(actual.is_some_and(|w| matches!(
+            w,
+            WxField::Wind {
+                direction: WindDirection::Calm,
+                ..
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_wind_from_str_variable_and_calm() {
+        let actual = wind_from_str("VRB02KT");
+        assert_eq!(
+            actual,
+            Some(WxField::Wind {
+                direction: WindDirection::Variable,
+                strength: Speed::new(2, SpeedUnit::Kt),
+                gusts: None,
+            })
+        );
+        let actual = wind_from_str("00000KT");
+        assert!(actual.is_some_and(|w| matches!(
+            w,
+            WxField::Wind {
+                direction: WindDirection::Calm,
+                ..
+            }
+        )));
+    }
+
     #[tokio::test]
     async fn test_colourise_wind_var() {
         let config = Config::default();
@@ -618,11 +2038,14 @@ mod tests {
     async fn test_colourise_temp() {
         let config = Config::default();
         let temp = WxField::Temperature {
-            temp: 20,
-            dewpoint: 10,
-            unit: TemperatureUnit::C,
+            temp: Temperature::new(20, TemperatureUnit::C),
+            dewpoint: Temperature::new(10, TemperatureUnit::C),
         };
-        let expected = colourise_temperature(20, 10, TemperatureUnit::C, &config);
+        let expected = colourise_temperature(
+            Temperature::new(20, TemperatureUnit::C),
+            Temperature::new(10, TemperatureUnit::C),
+            &config,
+        );
         let actual = temp.colourise(&config);
         assert_eq!(actual, expected);
     }
@@ -630,46 +2053,429 @@ mod tests {
     #[tokio::test]
     async fn test_colourise_qnh() {
         let config = Config::default();
-        let qnh = WxField::Qnh(1013, PressureUnit::Hpa);
-        let expected = colourise_qnh(1013, PressureUnit::Hpa, &config);
+        let qnh = WxField::Qnh(Pressure::new(1013, PressureUnit::Hpa));
+        let expected = colourise_qnh(Pressure::new(1013, PressureUnit::Hpa), &config);
         let actual = qnh.colourise(&config);
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_temp_negative_and_fahrenheit() {
+        let config = Config::default();
+        let actual = colourise_temperature(
+            Temperature::new(8, TemperatureUnit::C),
+            Temperature::new(-5, TemperatureUnit::C),
+            &config,
+        );
+        assert_eq!(actual.clear().to_string(), "08/M05");
+        let actual = colourise_temperature(
+            Temperature::new(41, TemperatureUnit::F),
+            Temperature::new(39, TemperatureUnit::F),
+            &config,
+        );
+        assert_eq!(actual.clear().to_string(), "41F/39F");
+    }
+
+    #[tokio::test]
+    async fn test_colourise_qnh_inhg() {
+        let config = Config::default();
+        let actual = colourise_qnh(Pressure::new(3002, PressureUnit::Inhg), &config);
+        assert_eq!(actual.fgcolor, Some(Color::Green));
+        assert_eq!(actual.clear().to_string(), "A3002");
+        let actual = colourise_qnh(Pressure::new(2966, PressureUnit::Inhg), &config);
+        assert_eq!(actual.fgcolor, Some(Color::Yellow));
+    }
+
+    #[tokio::test]
+    async fn test_colourise_wind_other_units() {
+        let config = Config::default();
+        let actual = colourise_wind(
+            WindDirection::Heading(270),
+            Speed::new(12, SpeedUnit::Mps),
+            None,
+            &config,
+        );
+        assert_eq!(actual.clear().to_string(), "27012MPS");
+    }
+
+    #[tokio::test]
+    async fn test_in_units() {
+        let units = Units {
+            pressure: PressureUnit::Inhg,
+            altitude: AltitudeUnit::M,
+            wind_speed: SpeedUnit::Kph,
+            temperature: TemperatureUnit::F,
+            distance: DistanceUnit::Mi,
+        };
+        let wind = wind_from_str("27010G20KT").unwrap().in_units(&units);
+        assert_eq!(
+            wind,
+            WxField::Wind {
+                direction: WindDirection::Heading(270),
+                strength: Speed::new(10, SpeedUnit::Kt),
+                gusts: Some(Speed::new(20, SpeedUnit::Kt)),
+            }
+        );
+        let WxField::Wind { strength, .. } = wind else {
+            panic!("Expected a wind field.");
+        };
+        assert_eq!(strength.unit, SpeedUnit::Kph);
+        assert_eq!(strength.value(), 19);
+
+        let qnh = WxField::Qnh(Pressure::new(1013, PressureUnit::Hpa)).in_units(&units);
+        let config = Config::default();
+        assert_eq!(qnh.colourise(&config).clear().to_string(), "A2991");
+        let temp = temp_from_str("M05/M10").unwrap().in_units(&units);
+        assert_eq!(temp.colourise(&config).clear().to_string(), "23F/14F");
+        let vis = visibility_from_str("4800").unwrap().in_units(&units);
+        assert_eq!(vis.colourise(&config).clear().to_string(), "3SM");
+        let clouds = clouds_from_str("BKN010").unwrap().in_units(&units);
+        assert_eq!(clouds.colourise(&config).clear().to_string(), "BKN305M");
+    }
+
+    #[tokio::test]
+    async fn test_colourise_display_units() {
+        let config = Config {
+            units: Some(Units {
+                wind_speed: SpeedUnit::Mph,
+                ..Units::default()
+            }),
+            ..Config::default()
+        };
+        let wind = wind_from_str("18020KT").unwrap();
+        let actual = wind.colourise(&config);
+        assert_eq!(actual.clear().to_string(), "18023MPH");
+    }
+
+    #[tokio::test]
+    async fn test_colourise_minima_with_units() {
+        let config = Config {
+            wind_maximum: Speed::new(20, SpeedUnit::Mph),
+            visibility_minimum: Distance::new(3, DistanceUnit::Mi),
+            ..Config::default()
+        };
+        // 18 kt are about 21 mph.
+        let wind = wind_from_str("18018KT").unwrap().colourise(&config);
+        assert!(wind.to_string().contains(&"18".red().to_string()));
+        let vis = visibility_from_str("4000").unwrap().colourise(&config);
+        assert_eq!(vis.fgcolor, Some(Color::Red));
+        let vis = visibility_from_str("5000").unwrap().colourise(&config);
+        assert_eq!(vis.fgcolor, Some(Color::Green));
+    }
+
+    #[tokio::test]
+    async fn test_assess() {
+        let config = Config::default();
+        let assess = |repr: &str| field_from_str(repr).unwrap().assess(&config);
+        assert_eq!(assess("27010KT"), Assessment::Good);
+        assert_eq!(assess("27010G25KT"), Assessment::Bad);
+        assert_eq!(assess("VRB05KT"), Assessment::Marginal);
+        assert_eq!(assess("3000"), Assessment::Marginal);
+        assert_eq!(assess("9999 1200NE"), Assessment::Bad);
+        assert_eq!(assess("12/11"), Assessment::Bad);
+        assert_eq!(assess("A2966"), Assessment::Marginal);
+        assert_eq!(assess("SCT040"), Assessment::Good);
+        assert_eq!(assess("SCT040TCU"), Assessment::Marginal);
+        assert_eq!(assess("BKN040CB"), Assessment::Bad);
+        assert_eq!(assess("VV///"), Assessment::Bad);
+        assert_eq!(assess("-RA"), Assessment::Marginal);
+        assert_eq!(assess("BR"), Assessment::Marginal);
+        assert_eq!(assess("FZDZ"), Assessment::Bad);
+        assert_eq!(assess("+SHRA"), Assessment::Bad);
+        assert_eq!(assess("RERA"), Assessment::Marginal);
+        assert_eq!(assess("WS R24"), Assessment::Bad);
+    }
+
+    #[tokio::test]
+    async fn test_assess_wx_code_hazards() {
+        let config = Config::default();
+        let assess = |repr: &str| field_from_str(repr).unwrap().assess(&config);
+        for hazard in [
+            "FC", "+FC", "VA", "SQ", "DS", "SS", "PO", "GR", "TS", "VCTS",
+        ] {
+            assert_eq!(assess(hazard), Assessment::Bad, "{hazard}");
+        }
+        for obscuration in ["FG", "BR", "MIFG", "BCFG", "DU", "SA", "FU"] {
+            assert_eq!(assess(obscuration), Assessment::Marginal, "{obscuration}");
+        }
+        assert_eq!(assess("HZ"), Assessment::Good);
+    }
+
+    #[tokio::test]
+    async fn test_wx_code_colour_by_assessment() {
+        assert_eq!(wx_code_colour(WxCode::Va), Color::Red);
+        assert_eq!(wx_code_colour(WxCode::Sq), Color::Red);
+        assert_eq!(wx_code_colour(WxCode::Fg), Color::Yellow);
+        assert_eq!(wx_code_colour(WxCode::Hz), Color::White);
+    }
+
+    #[tokio::test]
+    async fn test_report_json() {
+        let config = Config::default();
+        let metar = Metar::from_raw("KJFK 211451Z 27015G25KT 1 1/2SM BKN008CB M02/M08 A3002 NOSIG")
+            .unwrap();
+        let json = serde_json::to_value(metar.report(&config)).unwrap();
+        assert_eq!(json["station"], "KJFK");
+        assert_eq!(json["assessment"], "bad");
+        assert_eq!(json["fields"][1]["type"], "wind");
+        assert_eq!(json["fields"][1]["value"]["strength"]["value"], 15);
+        assert_eq!(json["fields"][1]["value"]["gusts"]["unit"], "kt");
+        assert_eq!(json["fields"][1]["assessment"], "good");
+        assert_eq!(json["fields"][2]["value"]["distance"]["value"], 1.5);
+        assert_eq!(json["fields"][2]["value"]["distance"]["unit"], "sm");
+        assert_eq!(json["fields"][3]["type"], "clouds");
+        assert_eq!(json["fields"][3]["value"]["cover"], "bkn");
+        assert_eq!(json["fields"][3]["value"]["base"]["value"], 800);
+        assert_eq!(json["fields"][3]["value"]["cloud_type"], "cb");
+        assert_eq!(json["fields"][3]["assessment"], "bad");
+        assert_eq!(json["fields"][5]["value"]["value"], 30.02);
+        assert_eq!(json["trends"][0]["change"], "nosig");
+    }
+
+    #[tokio::test]
+    async fn test_report_json_weather() {
+        let config = Config::default();
+        let metar =
+            Metar::from_raw("EDDK 211450Z 27010KT 9999 -SHRA VA RETS 12/05 Q1013 NOSIG").unwrap();
+        let json = serde_json::to_value(metar.report(&config)).unwrap();
+        assert_eq!(json["fields"][2]["value"]["distance"]["value"], 9999);
+        assert!(json["fields"][2]["value"]["distance"]["value"].is_i64());
+        assert_eq!(json["fields"][3]["type"], "wx_code");
+        assert_eq!(
+            json["fields"][3]["value"],
+            serde_json::json!({
+                "codes": ["ra"],
+                "intensity": "light",
+                "proximity": "on_station",
+                "descriptor": "sh",
+            })
+        );
+        assert_eq!(
+            json["fields"][4]["value"]["codes"],
+            serde_json::json!(["va"])
+        );
+        assert_eq!(json["fields"][4]["value"]["descriptor"], "none");
+        assert_eq!(json["fields"][4]["assessment"], "bad");
+        assert_eq!(json["fields"][5]["type"], "recent_weather");
+        assert_eq!(
+            json["fields"][5]["value"],
+            serde_json::json!({"codes": [], "descriptor": "ts"})
+        );
+        assert_eq!(json["fields"][7]["value"]["value"], 1013);
+        assert!(json["fields"][7]["value"]["value"].is_i64());
+    }
+
+    #[tokio::test]
+    async fn test_report_json_modifiers() {
+        let metar = Metar::from_raw("EDDK 211450Z AUTO 27010KT 9999 12/05 Q1013").unwrap();
+        let json = serde_json::to_value(metar.report(&Config::default())).unwrap();
+        assert_eq!(json["modifiers"], serde_json::json!(["auto"]));
+    }
+
+    #[tokio::test]
+    async fn test_report_display_units() {
+        let config = Config {
+            units: Some(Units {
+                wind_speed: SpeedUnit::Kph,
+                ..Units::default()
+            }),
+            ..Config::default()
+        };
+        let metar = Metar::from_raw("EDDK 211450Z 27010KT 9999 FEW040 12/05 Q1013").unwrap();
+        let json = serde_json::to_value(metar.report(&config)).unwrap();
+        assert_eq!(json["fields"][1]["value"]["strength"]["value"], 19);
+        assert_eq!(json["fields"][1]["value"]["strength"]["unit"], "kph");
+        assert_eq!(json["fields"][1]["assessment"], "good");
+    }
+
     #[tokio::test]
     async fn test_colourise_wxcode() {
         let config = Config::default();
         let wxcode = WxField::WxCode(
-            WxCode::Ra,
+            vec![WxCode::Ra],
             WxCodeIntensity::Moderate,
             WxCodeProximity::OnStation,
             WxCodeDescription::None,
         );
         let expected = colourise_wx_code(
-            &WxCode::Ra,
-            &WxCodeIntensity::Moderate,
-            &WxCodeProximity::OnStation,
-            &WxCodeDescription::None,
+            &[WxCode::Ra],
+            WxCodeIntensity::Moderate,
+            WxCodeProximity::OnStation,
+            WxCodeDescription::None,
             &config,
         );
         let actual = wxcode.colourise(&config);
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_wxcode_multiple() {
+        let config = Config::default();
+        let wxcode = wxcode_from_str("-FZRASN").unwrap().colourise(&config);
+        assert_eq!(wxcode.clear().to_string(), "-FZRASN");
+        let wxcode = wxcode_from_str("VCTS").unwrap().colourise(&config);
+        assert_eq!(wxcode.clear().to_string(), "VCTS");
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_nsw_trend() {
+        let metar =
+            Metar::from_raw("EDDK 101750Z 24015KT 9999 -TSRA BKN030CB 18/15 Q1008 BECMG NSW")
+                .unwrap();
+        assert_eq!(metar.trends[0].fields, vec![WxField::Nsw]);
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_remarks() {
+        let metar = Metar::from_raw(
+            "KDEN 101753Z 24015G35KT 10SM FEW080CB 28/08 A3002 RMK AO2 PK WND 25041/1712 LTG DSNT W TSB35 SLP105",
+        )
+        .unwrap();
+        let Some(WxField::Remarks(remarks)) = metar.fields.last() else {
+            panic!("No remarks decoded.");
+        };
+        assert_eq!(remarks.len(), 5);
+        assert_eq!(remarks[1].repr, "PK WND 25041/1712");
+        assert_eq!(remarks[2].repr, "LTG DSNT W");
+        assert_eq!(
+            remarks.iter().map(|r| r.severity).max(),
+            Some(remarks::RemarkSeverity::Warning)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_modifiers() {
+        let metar = Metar::from_raw("SPECI COR EDDK 210605Z AUTO 17014G28KT 3000 TSRA").unwrap();
+        assert_eq!(
+            metar.modifiers,
+            vec![
+                ReportModifier::Speci,
+                ReportModifier::Cor,
+                ReportModifier::Auto
+            ]
+        );
+        let metar = Metar::from_raw("EDDK 210550Z NIL").unwrap();
+        assert_eq!(metar.modifiers, vec![ReportModifier::Nil]);
+        assert!(metar.fields.len() == 1);
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_json_modifiers() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/eddk-pretty.json").unwrap(),
+        )
+        .unwrap();
+        let metar = Metar::from_json(&json, &Config::default()).unwrap();
+        assert_eq!(metar.modifiers, vec![ReportModifier::Auto]);
+    }
+
+    #[tokio::test]
+    async fn test_colorise_modifiers_in_header() {
+        let metar = Metar::from_raw("SPECI EDDK 210605Z 17014G28KT").unwrap();
+        let actual = metar.colorise(&Config::default()).clear().to_string();
+        assert!(actual.starts_with("EDDK SPECI 210605Z"));
+    }
+
+    #[tokio::test]
+    async fn test_report_modifier_colourise() {
+        assert_eq!(
+            ReportModifier::Speci.colourise(),
+            "SPECI".bold().black().on_yellow()
+        );
+        assert_eq!(
+            ReportModifier::Cor.colourise().bgcolor,
+            Some(Color::Magenta)
+        );
+    }
+
     #[tokio::test]
     async fn test_colourise_rmk() {
         let config = Config::default();
-        let rmk = WxField::Remarks("NONE".to_string());
-        let expected = "NONE".black().on_white();
+        let rmk = WxField::Remarks(remarks_from_tokens(&["NONE"]));
+        let expected: ColoredString =
+            format!("{} {}", "RMK".black().on_white(), "NONE".black().on_white()).into();
         let actual = rmk.colourise(&config);
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_rvr() {
+        let config = Config::default();
+        let rvr = rvr_from_str("R24/0600V1200/U").unwrap();
+        let expected = colourise_rvr(
+            "24",
+            &RvrValue {
+                range: 600,
+                limit: rvr::RvrLimit::Exact,
+            },
+            Some(&RvrValue {
+                range: 1200,
+                limit: rvr::RvrLimit::Exact,
+            }),
+            RvrTendency::Upward,
+            DistanceUnit::M,
+            &config,
+        );
+        let actual = rvr.colourise(&config);
+        assert_eq!(actual, expected);
+        assert!(actual.contains("R24/"));
+        assert!(actual.contains("0600V1200"));
+    }
+
+    #[tokio::test]
+    async fn test_rvr_colour() {
+        let config = Config::default();
+        let value = |range: i64| RvrValue {
+            range,
+            limit: rvr::RvrLimit::Exact,
+        };
+        let metres = DistanceUnit::M;
+        assert_eq!(rvr_colour(&value(300), None, metres, &config), Color::Red);
+        assert_eq!(
+            rvr_colour(&value(1000), None, metres, &config),
+            Color::Yellow
+        );
+        assert_eq!(
+            rvr_colour(&value(2000), None, metres, &config),
+            Color::Green
+        );
+        assert_eq!(
+            rvr_colour(&value(2000), Some(&value(400)), metres, &config),
+            Color::Red
+        );
+        assert_eq!(
+            rvr_colour(&value(1800), None, DistanceUnit::Ft, &config),
+            Color::Red
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_rvr() {
+        let metar =
+            Metar::from_raw("EDDK 210550Z 17004KT 0400 R14L/0550N R32R/P1500 FG VV001 05/05 Q1013")
+                .unwrap();
+        let rvrs = metar
+            .fields
+            .iter()
+            .filter(|f| matches!(f, WxField::RunwayVisualRange { .. }))
+            .count();
+        assert_eq!(rvrs, 2);
+    }
+
     #[tokio::test]
     async fn test_colourise_clouds() {
         let config = Config::default();
-        let clouds = WxField::Clouds(Clouds::Sct, 50);
-        let expected = colourise_clouds(&Clouds::Sct, 50, &config);
+        let clouds = WxField::Clouds(
+            Clouds::Sct,
+            Some(Altitude::new(5000, AltitudeUnit::Ft)),
+            CloudType::None,
+        );
+        let expected = colourise_clouds(
+            Clouds::Sct,
+            Some(Altitude::new(5000, AltitudeUnit::Ft)),
+            CloudType::None,
+            &config,
+        );
         let actual = clouds.colourise(&config);
         assert_eq!(actual, expected);
     }
@@ -684,11 +2490,32 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_clouds_three_digits() {
+        let config = Config::default();
+        let actual = colourise_clouds(
+            Clouds::Few,
+            Some(Altitude::new(800, AltitudeUnit::Ft)),
+            CloudType::None,
+            &config,
+        );
+        assert_eq!(actual.clear().to_string(), "FEW008");
+    }
+
     #[tokio::test]
     async fn test_colourise_clouds_marginal() {
         let config = Config::default();
-        let clouds = WxField::Clouds(Clouds::Ovc, 8);
-        let expected = colourise_clouds(&Clouds::Ovc, 8, &config);
+        let clouds = WxField::Clouds(
+            Clouds::Ovc,
+            Some(Altitude::new(800, AltitudeUnit::Ft)),
+            CloudType::None,
+        );
+        let expected = colourise_clouds(
+            Clouds::Ovc,
+            Some(Altitude::new(800, AltitudeUnit::Ft)),
+            CloudType::None,
+            &config,
+        );
         let actual = clouds.colourise(&config);
         assert_eq!(actual, expected);
     }
@@ -696,12 +2523,181 @@ mod tests {
     #[tokio::test]
     async fn test_colourise_clouds_bad() {
         let config = Config::default();
-        let clouds = WxField::Clouds(Clouds::Brk, 5);
-        let expected = colourise_clouds(&Clouds::Brk, 5, &config);
+        let clouds = WxField::Clouds(
+            Clouds::Bkn,
+            Some(Altitude::new(500, AltitudeUnit::Ft)),
+            CloudType::None,
+        );
+        let expected = colourise_clouds(
+            Clouds::Bkn,
+            Some(Altitude::new(500, AltitudeUnit::Ft)),
+            CloudType::None,
+            &config,
+        );
         let actual = clouds.colourise(&config);
         assert_eq!(actual, expected);
     }
 
+    #[tokio::test]
+    async fn test_colourise_clouds_cb() {
+        let config = Config::default();
+        let clouds = WxField::Clouds(
+            Clouds::Bkn,
+            Some(Altitude::new(800, AltitudeUnit::Ft)),
+            CloudType::Cb,
+        );
+        let actual = clouds.colourise(&config);
+        assert!(actual.contains("BKN"));
+        assert!(actual.contains("8"));
+        assert!(actual.contains("CB"));
+    }
+
+    #[tokio::test]
+    async fn test_cloud_colours_match_assessment() {
+        let config = Config::default();
+        let ft = |value| Some(Altitude::new(value, AltitudeUnit::Ft));
+        let cases = [
+            (Clouds::Ovc, ft(30000), CloudType::None, Assessment::Good),
+            (Clouds::Bkn, ft(1200), CloudType::None, Assessment::Marginal),
+            (Clouds::Few, ft(500), CloudType::None, Assessment::Bad),
+            (Clouds::Sct, ft(5000), CloudType::Tcu, Assessment::Marginal),
+            (Clouds::Vv, None, CloudType::None, Assessment::Bad),
+        ];
+        for (cloud, alt, cloud_type, expected) in cases {
+            let assessment = WxField::Clouds(cloud, alt, cloud_type).assess(&config);
+            assert_eq!(assessment, expected);
+            let shown = [
+                cloud_cover_colour(cloud, alt, &config),
+                cloud_base_colour(cloud, alt, &config),
+                cloud_type_colour(cloud_type),
+            ]
+            .into_iter()
+            .map(Assessment::from)
+            .max();
+            assert_eq!(shown, Some(assessment));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_colourise_clouds_no_altitude() {
+        let config = Config::default();
+        let actual = WxField::Clouds(Clouds::Nsc, None, CloudType::None).colourise(&config);
+        assert_eq!(actual.clone().clear().to_string(), "NSC");
+        let actual = WxField::Clouds(Clouds::Vv, None, CloudType::None).colourise(&config);
+        assert_eq!(actual.clear().to_string(), "VV///");
+    }
+
+    #[tokio::test]
+    async fn test_colorise_remarks_info() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/apiary_pretty.json").unwrap(),
+        )
+        .unwrap();
+        let metar = Metar::from_json(&json, &Config::default()).unwrap();
+        let actual = metar.colorise(&Config::default()).clear().to_string();
+        let (_, decoded) = actual.split_once('\n').unwrap();
+        assert!(decoded.trim().starts_with("RMK SLP 1004.2 hPa"));
+    }
+
+    #[tokio::test]
+    async fn test_colourise_runway_state() {
+        let config = Config::default();
+        let actual = runway_state_from_str("R24/290155")
+            .unwrap()
+            .colourise(&config);
+        assert_eq!(actual.fgcolor, Some(Color::Yellow));
+        assert_eq!(
+            actual.clear().to_string(),
+            "R24: wet, 51-100%, 1mm, friction 0.55"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runway_state_colour() {
+        assert_eq!(
+            runway_state_colour(
+                RunwayDeposit::ClearDry,
+                DepositDepth::Millimetres(0),
+                BrakingAction::Friction(25)
+            ),
+            Color::Red
+        );
+        assert_eq!(
+            runway_state_colour(
+                RunwayDeposit::Damp,
+                DepositDepth::NotReported,
+                BrakingAction::Good
+            ),
+            Color::Green
+        );
+        assert_eq!(
+            runway_state_colour(
+                RunwayDeposit::DrySnow,
+                DepositDepth::NotOperational,
+                BrakingAction::NotReported
+            ),
+            Color::Red
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_runway_state() {
+        let metar =
+            Metar::from_raw("ENGM 101220Z 01005KT 9999 FEW030 M05/M08 Q1020 R01L/420542").unwrap();
+        assert!(metar.fields.contains(&WxField::RunwayState {
+            runway: "01L".to_string(),
+            deposit: RunwayDeposit::DrySnow,
+            extent: RunwayExtent::UpTo25,
+            depth: DepositDepth::Millimetres(5),
+            braking: BrakingAction::Friction(42),
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_colourise_wind_shear() {
+        let config = Config::default();
+        let actual = WxField::WindShear(WindShearRunway::All).colourise(&config);
+        assert_eq!(actual.bgcolor, Some(Color::Red));
+        assert_eq!(actual.clear().to_string(), "WS ALL RWY");
+    }
+
+    #[tokio::test]
+    async fn test_colourise_recent_weather() {
+        let config = Config::default();
+        let actual =
+            WxField::RecentWeather(vec![WxCode::Ra], WxCodeDescription::Ts).colourise(&config);
+        assert_eq!(actual.bgcolor, Some(Color::Red));
+        assert_eq!(actual.clear().to_string(), "RETSRA");
+        let actual =
+            WxField::RecentWeather(vec![WxCode::Sn], WxCodeDescription::None).colourise(&config);
+        assert_eq!(actual.bgcolor, Some(Color::Yellow));
+    }
+
+    #[tokio::test]
+    async fn test_metar_from_raw_recent_weather_and_wind_shear() {
+        let metar = Metar::from_raw(
+            "EDDF 101220Z 24025G40KT 9999 RETSRA FEW040CB 18/12 Q1002 WS R25C WS ALL RWY NOSIG",
+        )
+        .unwrap();
+        assert!(metar.fields.contains(&WxField::RecentWeather(
+            vec![WxCode::Ra],
+            WxCodeDescription::Ts
+        )));
+        assert!(
+            metar
+                .fields
+                .contains(&WxField::WindShear(WindShearRunway::Runway(
+                    "25C".to_string()
+                )))
+        );
+        assert!(
+            metar
+                .fields
+                .contains(&WxField::WindShear(WindShearRunway::All))
+        );
+        assert_eq!(metar.trends.len(), 1);
+    }
+
     // #[test]
     // fn test_colourise_wxcode_sn() {
     //     let config = Config::default();
@@ -10,54 +10,93 @@
 // limitations under the License.
 // WxFetch - metar/clouds.rs
 
-use std::{fmt::Display, str::FromStr};
+//! Cloud cover and convective cloud types of cloud groups.
 
-use super::WxField;
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use super::units::{Altitude, AltitudeUnit};
+use super::{WxField, build_regex};
 use anyhow::anyhow;
 use regex::Regex;
+use serde::Serialize;
 use serde_json::Value;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(PartialEq, Eq, Debug, EnumIter)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, EnumIter, Serialize)]
+#[serde(rename_all = "snake_case")]
 /// Describes a cloud layer.
 pub enum Clouds {
     /// Sky clear.
     Skc,
     /// Few. Up to 2 / 8 coverage.
     Few,
-    // Scattered. 3 - 4 / 8 coverage.
+    /// Scattered. 3 - 4 / 8 coverage.
     Sct,
     /// Broken. 5 - 7 / 8 coverage.
-    Brk,
+    Bkn,
     /// Overcast. 8 / 8 coverage.
     Ovc,
+    /// Sky obscured, altitude gives the vertical visibility.
+    Vv,
+    /// No significant cloud (none below 5000ft and no CB or TCU).
+    Nsc,
+    /// No cloud detected (automated reports only).
+    Ncd,
+    /// No cloud below 12000ft detected (automated reports only).
+    Clr,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Convective cloud type reported with a cloud layer.
+pub enum CloudType {
+    /// No type given.
+    None,
+    /// Cumulonimbus.
+    Cb,
+    /// Towering cumulus.
+    Tcu,
+    /// Type could not be observed (automated reports only).
+    Unknown,
 }
 
 /// Parses a METAR in JSON form and returns a `Vec` of `MetarField::Clouds` describing the cloud information contained.
-pub fn get_clouds_from_json(json: &Value) -> Vec<WxField> {
+pub(crate) fn get_clouds_from_json(json: &Value) -> Vec<WxField> {
     let mut result: Vec<WxField> = Vec::new();
     if let Some(wxcodes) = json.get("clouds").and_then(|x| x.as_array()) {
         for code in wxcodes {
-            if let Some(repr) = code.get("repr").and_then(|x| x.as_str()) {
-                if let Some(cloud) = clouds_from_str(repr) {
-                    result.push(cloud);
-                }
+            if let Some(repr) = code.get("repr").and_then(|x| x.as_str())
+                && let Some(cloud) = clouds_from_str(repr)
+            {
+                result.push(cloud);
             }
         }
     }
     result
 }
 
-/// From a METAR compliant cloud code representation string (`&str`) parses a `MetarField::Cloud`.
-fn clouds_from_str(repr: &str) -> Option<WxField> {
-    let regex = format!("(?<obscuration>{})(?<level>\\d*)", Clouds::get_regex());
-    let regex = Regex::new(&regex)
-        .expect("Creating RegEx pattern failed. This is likely a software bug, please report it.");
-    let matches = regex.captures(repr)?;
+/// Cloud group like "BKN008CB" or "VV///".
+static CLOUDS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(&format!(
+        "^(?<obscuration>{})(?<level>\\d+|///)?(?<type>CB|TCU|///)?$",
+        Clouds::get_regex()
+    ))
+});
+
+/// From a METAR compliant cloud code representation string (`&str`) such as "BKN008CB" or "VV///" parses a `MetarField::Cloud`. Altitudes given as "///" could not be observed and are returned as None.
+pub(crate) fn clouds_from_str(repr: &str) -> Option<WxField> {
+    let matches = CLOUDS_REGEX.captures(repr)?;
     let obscuration: Clouds = matches["obscuration"].parse().ok()?;
-    let level: i64 = matches["level"].parse().unwrap_or(0);
-    Some(WxField::Clouds(obscuration, level))
+    let level: Option<Altitude> = matches
+        .name("level")
+        .and_then(|l| l.as_str().parse::<i64>().ok())
+        .map(|hundreds| Altitude::new(hundreds * 100, AltitudeUnit::Ft));
+    let cloud_type: CloudType = matches
+        .name("type")
+        .map_or(Ok(CloudType::None), |t| t.as_str().parse())
+        .ok()?;
+    Some(WxField::Clouds(obscuration, level, cloud_type))
 }
 
 impl Clouds {
@@ -83,21 +122,55 @@ impl FromStr for Clouds {
             "skc" => Ok(Self::Skc),
             "few" => Ok(Self::Few),
             "sct" => Ok(Self::Sct),
-            "brk" => Ok(Self::Brk),
+            "bkn" => Ok(Self::Bkn),
             "ovc" => Ok(Self::Ovc),
+            "vv" => Ok(Self::Vv),
+            "nsc" => Ok(Self::Nsc),
+            "ncd" => Ok(Self::Ncd),
+            "clr" => Ok(Self::Clr),
             _ => Err(anyhow!("Invalid cloud obscuration {s}.")),
         }
     }
 }
 
+impl FromStr for CloudType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" => Ok(Self::None),
+            "cb" => Ok(Self::Cb),
+            "tcu" => Ok(Self::Tcu),
+            "///" => Ok(Self::Unknown),
+            _ => Err(anyhow!("Invalid cloud type {s}.")),
+        }
+    }
+}
+
 impl Display for Clouds {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str_repr: &str = match self {
             Clouds::Skc => "SKC",
             Clouds::Few => "FEW",
             Clouds::Sct => "SCT",
-            Clouds::Brk => "BRK",
+            Clouds::Bkn => "BKN",
             Clouds::Ovc => "OVC",
+            Clouds::Vv => "VV",
+            Clouds::Nsc => "NSC",
+            Clouds::Ncd => "NCD",
+            Clouds::Clr => "CLR",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl Display for CloudType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            CloudType::None => "",
+            CloudType::Cb => "CB",
+            CloudType::Tcu => "TCU",
+            CloudType::Unknown => "///",
         };
         write!(f, "{str_repr}")
     }
@@ -111,33 +184,92 @@ mod tests {
 
     use crate::metar::WxField;
 
-    use super::{clouds_from_str, get_clouds_from_json, Clouds};
+    use super::{CloudType, Clouds, clouds_from_str, get_clouds_from_json};
+    use crate::metar::units::{Altitude, AltitudeUnit};
 
     #[tokio::test]
     async fn test_get_regex() {
-        let expected: &str = "SKC|FEW|SCT|BRK|OVC";
+        let expected: &str = "SKC|FEW|SCT|BKN|OVC|VV|NSC|NCD|CLR";
         let actual = Clouds::get_regex();
         assert_eq!(expected, actual);
     }
 
     #[tokio::test]
     async fn test_clouds_from_str() {
-        let expected = WxField::Clouds(Clouds::Skc, 0);
+        let expected = WxField::Clouds(Clouds::Skc, None, CloudType::None);
         let actual = clouds_from_str("SKC");
         assert_eq!(Some(expected), actual);
     }
 
     #[tokio::test]
     async fn test_clouds_from_str_sct() {
-        let expected = WxField::Clouds(Clouds::Sct, 50);
+        let expected = WxField::Clouds(
+            Clouds::Sct,
+            Some(Altitude::new(5000, AltitudeUnit::Ft)),
+            CloudType::None,
+        );
         let actual = clouds_from_str("SCT50");
         assert_eq!(Some(expected), actual);
     }
 
+    #[tokio::test]
+    async fn test_clouds_from_str_cb() {
+        let expected = WxField::Clouds(
+            Clouds::Bkn,
+            Some(Altitude::new(800, AltitudeUnit::Ft)),
+            CloudType::Cb,
+        );
+        let actual = clouds_from_str("BKN008CB");
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[tokio::test]
+    async fn test_clouds_from_str_tcu() {
+        let expected = WxField::Clouds(
+            Clouds::Sct,
+            Some(Altitude::new(2500, AltitudeUnit::Ft)),
+            CloudType::Tcu,
+        );
+        let actual = clouds_from_str("SCT025TCU");
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[tokio::test]
+    async fn test_clouds_from_str_vv_unknown() {
+        let expected = WxField::Clouds(Clouds::Vv, None, CloudType::None);
+        let actual = clouds_from_str("VV///");
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[tokio::test]
+    async fn test_clouds_from_str_unknown_type() {
+        let expected = WxField::Clouds(
+            Clouds::Ovc,
+            Some(Altitude::new(1200, AltitudeUnit::Ft)),
+            CloudType::Unknown,
+        );
+        let actual = clouds_from_str("OVC012///");
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[tokio::test]
+    async fn test_clouds_from_str_no_cloud() {
+        for (repr, cover) in [
+            ("NSC", Clouds::Nsc),
+            ("NCD", Clouds::Ncd),
+            ("CLR", Clouds::Clr),
+        ] {
+            let expected = WxField::Clouds(cover, None, CloudType::None);
+            assert_eq!(Some(expected), clouds_from_str(repr));
+        }
+    }
+
     #[tokio::test]
     async fn test_clouds_from_str_err() {
         let actual = clouds_from_str("OCC33");
         assert!(actual.is_none());
+        assert!(clouds_from_str("BRK100").is_none());
+        assert!(clouds_from_str("FEW020XY").is_none());
     }
 
     #[tokio::test]
@@ -149,13 +281,25 @@ mod tests {
     #[tokio::test]
     async fn test_get_clouds() {
         let json: Value = Value::from_str(
-            "{\"clouds\":[{\"repr\": \"SCT050\"},{\"repr\": \"BRK100\"},{\"repr\": \"OVC200\"}]}",
+            "{\"clouds\":[{\"repr\": \"SCT050\"},{\"repr\": \"BKN100\"},{\"repr\": \"OVC200\"}]}",
         )
         .unwrap();
         let expected: Vec<WxField> = vec![
-            WxField::Clouds(Clouds::Sct, 50),
-            WxField::Clouds(Clouds::Brk, 100),
-            WxField::Clouds(Clouds::Ovc, 200),
+            WxField::Clouds(
+                Clouds::Sct,
+                Some(Altitude::new(5000, AltitudeUnit::Ft)),
+                CloudType::None,
+            ),
+            WxField::Clouds(
+                Clouds::Bkn,
+                Some(Altitude::new(10000, AltitudeUnit::Ft)),
+                CloudType::None,
+            ),
+            WxField::Clouds(
+                Clouds::Ovc,
+                Some(Altitude::new(20000, AltitudeUnit::Ft)),
+                CloudType::None,
+            ),
         ];
         let actual = get_clouds_from_json(&json);
         assert_eq!(expected, actual);
@@ -0,0 +1,515 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/remarks.rs
+
+//! Decoding of the remarks section of METARs, rating remarks by their severity.
+
+use std::sync::LazyLock;
+
+use colored::{ColoredString, Colorize};
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+use super::build_regex;
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+/// A single decoded group (or run of groups) of the remarks section.
+pub struct Remark {
+    /// Groups as given in the report.
+    pub repr: String,
+    /// Decoded contents.
+    pub kind: RemarkKind,
+    /// How prominently this remark should be displayed.
+    pub severity: RemarkSeverity,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Importance of a remark.
+pub enum RemarkSeverity {
+    /// Routine information.
+    Routine,
+    /// Worth a second look.
+    Caution,
+    /// Significant for the operation, e.g. lightning.
+    Warning,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Remark groups used mostly in North American METARs. Temperatures are in tenths of °C, pressures in tenths of hPa, precipitation in hundredths of an inch and times as (hh)mm in UTC.
+pub enum RemarkKind {
+    /// Automated station without (AO1) or with (AO2) precipitation discriminator.
+    Automated {
+        /// Whether the station can tell rain from snow (AO2).
+        precipitation_sensor: bool,
+    },
+    /// Sea-level pressure (`SLPnnn`).
+    SeaLevelPressure(i64),
+    /// Temperature and dewpoint to a tenth of a degree (Tsnnnsnnn).
+    TemperatureDecimal {
+        /// Temperature.
+        temp: i64,
+        /// Dewpoint, if reported.
+        dewpoint: Option<i64>,
+    },
+    /// Precipitation during the last hour (Pnnnn).
+    HourlyPrecipitation(Option<i64>),
+    /// Precipitation during the last three or six hours (6nnnn).
+    Precipitation6Hours(Option<i64>),
+    /// Precipitation during the last 24 hours (7nnnn).
+    Precipitation24Hours(Option<i64>),
+    /// Maximum and minimum temperature during the last 24 hours (4snnnsnnn).
+    Temperature24Hours {
+        /// Maximum temperature.
+        maximum: i64,
+        /// Minimum temperature.
+        minimum: i64,
+    },
+    /// Peak wind since the last routine report (PK WND dddff/hhmm).
+    PeakWind {
+        /// Direction in degrees.
+        direction: i64,
+        /// Speed in knots.
+        speed: i64,
+        /// Time it occurred.
+        time: u32,
+    },
+    /// Wind shift (WSHFT hhmm), possibly due to a frontal passage.
+    WindShift {
+        /// Time the shift began.
+        time: u32,
+        /// Whether it was due to a frontal passage (FROPA).
+        frontal_passage: bool,
+    },
+    /// Frontal passage (FROPA).
+    FrontalPassage,
+    /// Lightning, with frequency (OCNL, FRQ, CONS) and location or directions (DSNT, VC, ALQDS, NE, ...).
+    Lightning {
+        /// How often lightning was observed, if reported.
+        frequency: Option<String>,
+        /// Where lightning was observed.
+        location: Vec<String>,
+    },
+    /// Precipitation not reaching the ground (VIRGA), optionally with direction.
+    Virga(Option<String>),
+    /// Begin and end times of a weather phenomenon, e.g. TSB05E30 or RAB10.
+    BeginEnd {
+        /// The weather phenomenon, e.g. TS or RA.
+        weather: String,
+        /// Its begin and end times, in order.
+        events: Vec<WeatherEvent>,
+    },
+    /// Station requires maintenance ($).
+    MaintenanceRequired,
+    /// Anything not decoded.
+    Unknown,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Begin or end of a weather phenomenon, given as (hh)mm.
+pub enum WeatherEvent {
+    /// The phenomenon began (B).
+    Began(u32),
+    /// The phenomenon ended (E).
+    Ended(u32),
+}
+
+impl Remark {
+    fn new(repr: &[&str], kind: RemarkKind) -> Self {
+        let severity = kind.severity();
+        Remark {
+            repr: repr.join(" "),
+            kind,
+            severity,
+        }
+    }
+
+    /// The raw remark, highlighted by its severity.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        match self.severity {
+            RemarkSeverity::Routine => self.repr.black().on_white(),
+            RemarkSeverity::Caution => self.repr.black().on_yellow(),
+            RemarkSeverity::Warning => self.repr.bold().bright_white().on_red(),
+        }
+    }
+}
+
+impl RemarkKind {
+    /// Default severity for this kind of remark.
+    fn severity(&self) -> RemarkSeverity {
+        match self {
+            RemarkKind::Lightning { .. } => RemarkSeverity::Warning,
+            RemarkKind::BeginEnd { weather, .. } if weather.contains("TS") => {
+                RemarkSeverity::Warning
+            }
+            RemarkKind::PeakWind { .. }
+            | RemarkKind::WindShift { .. }
+            | RemarkKind::FrontalPassage
+            | RemarkKind::Virga(_)
+            | RemarkKind::MaintenanceRequired => RemarkSeverity::Caution,
+            _ => RemarkSeverity::Routine,
+        }
+    }
+}
+
+/// Decodes the groups of a remarks section. The leading RMK is skipped, consecutive groups that cannot be decoded are combined into a single `RemarkKind::Unknown`.
+pub(crate) fn remarks_from_tokens(tokens: &[&str]) -> Vec<Remark> {
+    let tokens: Vec<&str> = tokens.iter().copied().filter(|t| *t != "RMK").collect();
+    let mut remarks: Vec<Remark> = Vec::new();
+    let mut unknown: Vec<&str> = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        if let Some((kind, consumed)) = remark_from_tokens(&tokens[index..]) {
+            if !unknown.is_empty() {
+                remarks.push(Remark::new(&unknown, RemarkKind::Unknown));
+                unknown.clear();
+            }
+            remarks.push(Remark::new(&tokens[index..index + consumed], kind));
+            index += consumed;
+        } else {
+            unknown.push(tokens[index]);
+            index += 1;
+        }
+    }
+    if !unknown.is_empty() {
+        remarks.push(Remark::new(&unknown, RemarkKind::Unknown));
+    }
+    remarks
+}
+
+/// Decodes the remark starting at the first token, returning it along with the number of tokens used.
+fn remark_from_tokens(tokens: &[&str]) -> Option<(RemarkKind, usize)> {
+    let first = *tokens.first()?;
+    match first {
+        "AO1" => {
+            return Some((
+                RemarkKind::Automated {
+                    precipitation_sensor: false,
+                },
+                1,
+            ));
+        }
+        "AO2" => {
+            return Some((
+                RemarkKind::Automated {
+                    precipitation_sensor: true,
+                },
+                1,
+            ));
+        }
+        "FROPA" => return Some((RemarkKind::FrontalPassage, 1)),
+        "$" => return Some((RemarkKind::MaintenanceRequired, 1)),
+        "PK" => return peak_wind_from_tokens(tokens),
+        "WSHFT" => return wind_shift_from_tokens(tokens),
+        "VIRGA" => {
+            return Some(match tokens.get(1).filter(|t| is_direction(t)) {
+                Some(direction) => (RemarkKind::Virga(Some(direction.to_string())), 2),
+                None => (RemarkKind::Virga(None), 1),
+            });
+        }
+        _ => {}
+    }
+    if let Some(result) = lightning_from_tokens(tokens) {
+        return Some(result);
+    }
+    let kind = slp_from_str(first)
+        .or_else(|| temp_decimal_from_str(first))
+        .or_else(|| precipitation_from_str(first))
+        .or_else(|| temp_24_hours_from_str(first))
+        .or_else(|| begin_end_from_str(first))?;
+    Some((kind, 1))
+}
+
+/// Sea-level pressure like "SLP105".
+static SLP_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^SLP(?<pressure>\d{3})$"));
+/// Temperature and dewpoint in tenths of a degree like "T02330139".
+static TEMP_DECIMAL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^T(?<ts>[01])(?<temp>\d{3})((?<ds>[01])(?<dew>\d{3}))?$"));
+/// Precipitation amount like "P0009", "60012" or "70125".
+static PRECIPITATION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<group>[P67])(?<amount>\d{4}|////)$"));
+/// 24-hour maximum and minimum temperature like "401001015".
+static TEMP_24_HOURS_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^4(?<maxs>[01])(?<max>\d{3})(?<mins>[01])(?<min>\d{3})$"));
+/// Begin and end times of weather like "RAB15E30".
+static BEGIN_END_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<weather>[A-Z]{2,6}?)(?<events>([BE]\d{2}(\d{2})?)+)$"));
+/// A single begin or end time within a begin/end group, like "B15".
+static EVENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"(?<kind>[BE])(?<time>\d{2}(\d{2})?)"));
+/// Peak wind like "25041/1712".
+static PEAK_WIND_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<direction>\d{3})(?<speed>\d{2,3})/(?<time>\d{2}(\d{2})?)$"));
+/// Time given as minutes or hours and minutes, like "30" or "1715".
+static TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^\d{2}(\d{2})?$"));
+/// Lightning types like "LTG" or "LTGICCG".
+static LIGHTNING_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^LTG(IC|CC|CG|CA)*$"));
+/// Compass direction or range of directions like "NE" or "SW-N".
+static DIRECTION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(N|NE|E|SE|S|SW|W|NW)(-(N|NE|E|SE|S|SW|W|NW))*$"));
+
+/// Parses a signed temperature in tenths of a degree as used by T- and 4-groups ("1" indicates below zero).
+fn signed_tenths(sign: &str, value: &str) -> Option<i64> {
+    let value: i64 = value.parse().ok()?;
+    Some(if sign == "1" { -value } else { value })
+}
+
+fn slp_from_str(repr: &str) -> Option<RemarkKind> {
+    let matches = SLP_REGEX.captures(repr)?;
+    let tenths: i64 = matches["pressure"].parse().ok()?;
+    let base = if tenths < 500 { 10000 } else { 9000 };
+    Some(RemarkKind::SeaLevelPressure(base + tenths))
+}
+
+fn temp_decimal_from_str(repr: &str) -> Option<RemarkKind> {
+    let matches = TEMP_DECIMAL_REGEX.captures(repr)?;
+    let dewpoint = match (matches.name("ds"), matches.name("dew")) {
+        (Some(sign), Some(dew)) => Some(signed_tenths(sign.as_str(), dew.as_str())?),
+        _ => None,
+    };
+    Some(RemarkKind::TemperatureDecimal {
+        temp: signed_tenths(&matches["ts"], &matches["temp"])?,
+        dewpoint,
+    })
+}
+
+fn precipitation_from_str(repr: &str) -> Option<RemarkKind> {
+    let matches = PRECIPITATION_REGEX.captures(repr)?;
+    let amount: Option<i64> = matches["amount"].parse().ok();
+    match &matches["group"] {
+        "P" => Some(RemarkKind::HourlyPrecipitation(amount)),
+        "6" => Some(RemarkKind::Precipitation6Hours(amount)),
+        _ => Some(RemarkKind::Precipitation24Hours(amount)),
+    }
+}
+
+fn temp_24_hours_from_str(repr: &str) -> Option<RemarkKind> {
+    let matches = TEMP_24_HOURS_REGEX.captures(repr)?;
+    Some(RemarkKind::Temperature24Hours {
+        maximum: signed_tenths(&matches["maxs"], &matches["max"])?,
+        minimum: signed_tenths(&matches["mins"], &matches["min"])?,
+    })
+}
+
+fn begin_end_from_str(repr: &str) -> Option<RemarkKind> {
+    let matches = BEGIN_END_REGEX.captures(repr)?;
+    let events = EVENT_REGEX
+        .captures_iter(&matches["events"])
+        .map(|event: Captures| {
+            let time: u32 = event["time"].parse().ok()?;
+            Some(match &event["kind"] {
+                "B" => WeatherEvent::Began(time),
+                _ => WeatherEvent::Ended(time),
+            })
+        })
+        .collect::<Option<Vec<WeatherEvent>>>()?;
+    Some(RemarkKind::BeginEnd {
+        weather: matches["weather"].to_string(),
+        events,
+    })
+}
+
+fn peak_wind_from_tokens(tokens: &[&str]) -> Option<(RemarkKind, usize)> {
+    if tokens.get(1) != Some(&"WND") {
+        return None;
+    }
+    let matches = PEAK_WIND_REGEX.captures(tokens.get(2)?)?;
+    Some((
+        RemarkKind::PeakWind {
+            direction: matches["direction"].parse().ok()?,
+            speed: matches["speed"].parse().ok()?,
+            time: matches["time"].parse().ok()?,
+        },
+        3,
+    ))
+}
+
+fn wind_shift_from_tokens(tokens: &[&str]) -> Option<(RemarkKind, usize)> {
+    let time_str = tokens.get(1)?;
+    if !TIME_REGEX.is_match(time_str) {
+        return None;
+    }
+    let frontal_passage = tokens.get(2) == Some(&"FROPA");
+    Some((
+        RemarkKind::WindShift {
+            time: time_str.parse().ok()?,
+            frontal_passage,
+        },
+        if frontal_passage { 3 } else { 2 },
+    ))
+}
+
+/// Lightning remarks such as "LTG DSNT NE", "OCNL LTGICCG VC NW-N" or "FRQ LTG ALQDS".
+fn lightning_from_tokens(tokens: &[&str]) -> Option<(RemarkKind, usize)> {
+    let (frequency, start) = match tokens.first() {
+        Some(freq @ (&"OCNL" | &"FRQ" | &"CONS")) => (Some(freq.to_string()), 1),
+        _ => (None, 0),
+    };
+    if !LIGHTNING_REGEX.is_match(tokens.get(start)?) {
+        return None;
+    }
+    let location: Vec<String> = tokens[start + 1..]
+        .iter()
+        .take_while(|t| matches!(**t, "DSNT" | "VC" | "OHD" | "ALQDS" | "AND") || is_direction(t))
+        .map(ToString::to_string)
+        .collect();
+    let consumed = start + 1 + location.len();
+    Some((
+        RemarkKind::Lightning {
+            frequency,
+            location,
+        },
+        consumed,
+    ))
+}
+
+/// True for compass directions and ranges of them, e.g. "NE" or "SW-N".
+fn is_direction(repr: &str) -> bool {
+    DIRECTION_REGEX.is_match(repr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(repr: &str) -> Vec<RemarkKind> {
+        let tokens: Vec<&str> = repr.split_whitespace().collect();
+        remarks_from_tokens(&tokens)
+            .into_iter()
+            .map(|remark| remark.kind)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_remarks_from_tokens_us() {
+        let expected = vec![
+            RemarkKind::Automated {
+                precipitation_sensor: true,
+            },
+            RemarkKind::PeakWind {
+                direction: 360,
+                speed: 29,
+                time: 1550,
+            },
+            RemarkKind::BeginEnd {
+                weather: "RA".to_string(),
+                events: vec![WeatherEvent::Began(10)],
+            },
+            RemarkKind::SeaLevelPressure(10042),
+            RemarkKind::HourlyPrecipitation(Some(0)),
+            RemarkKind::TemperatureDecimal {
+                temp: 100,
+                dewpoint: Some(67),
+            },
+        ];
+        assert_eq!(
+            kinds("RMK AO2 PK WND 36029/1550 RAB10 SLP042 P0000 T01000067"),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remarks_from_tokens_unknown_combined() {
+        let remarks = remarks_from_tokens(&["RWY", "UNAVAILABLE", "AO1", "FEW150"]);
+        assert_eq!(remarks.len(), 3);
+        assert_eq!(remarks[0].repr, "RWY UNAVAILABLE");
+        assert_eq!(remarks[0].kind, RemarkKind::Unknown);
+        assert_eq!(remarks[2].repr, "FEW150");
+    }
+
+    #[tokio::test]
+    async fn test_remarks_lightning() {
+        let remarks = remarks_from_tokens(&["OCNL", "LTGICCG", "VC", "NW-N", "TSB05E30", "$"]);
+        assert_eq!(
+            remarks[0].kind,
+            RemarkKind::Lightning {
+                frequency: Some("OCNL".to_string()),
+                location: vec!["VC".to_string(), "NW-N".to_string()],
+            }
+        );
+        assert_eq!(remarks[0].repr, "OCNL LTGICCG VC NW-N");
+        assert_eq!(remarks[0].severity, RemarkSeverity::Warning);
+        assert_eq!(
+            remarks[1].kind,
+            RemarkKind::BeginEnd {
+                weather: "TS".to_string(),
+                events: vec![WeatherEvent::Began(5), WeatherEvent::Ended(30)],
+            }
+        );
+        assert_eq!(remarks[1].severity, RemarkSeverity::Warning);
+        assert_eq!(remarks[2].kind, RemarkKind::MaintenanceRequired);
+        assert_eq!(remarks[2].severity, RemarkSeverity::Caution);
+    }
+
+    #[tokio::test]
+    async fn test_remarks_wind_shift_virga() {
+        let expected = vec![
+            RemarkKind::WindShift {
+                time: 1715,
+                frontal_passage: true,
+            },
+            RemarkKind::Virga(Some("SW".to_string())),
+            RemarkKind::FrontalPassage,
+        ];
+        assert_eq!(kinds("WSHFT 1715 FROPA VIRGA SW FROPA"), expected);
+    }
+
+    #[tokio::test]
+    async fn test_remarks_groups() {
+        let expected = vec![
+            RemarkKind::Precipitation6Hours(Some(12)),
+            RemarkKind::Precipitation24Hours(None),
+            RemarkKind::Temperature24Hours {
+                maximum: 114,
+                minimum: -21,
+            },
+            RemarkKind::SeaLevelPressure(9987),
+            RemarkKind::TemperatureDecimal {
+                temp: -12,
+                dewpoint: None,
+            },
+        ];
+        assert_eq!(kinds("60012 7//// 401141021 SLP987 T1012"), expected);
+    }
+
+    #[tokio::test]
+    async fn test_remarks_invalid_groups() {
+        assert_eq!(kinds("PK 36029/1550"), vec![RemarkKind::Unknown]);
+        assert_eq!(kinds("WSHFT"), vec![RemarkKind::Unknown]);
+        assert_eq!(kinds("SLP04"), vec![RemarkKind::Unknown]);
+    }
+
+    #[tokio::test]
+    async fn test_remark_colourise() {
+        let remark = Remark::new(
+            &["AO2"],
+            RemarkKind::Automated {
+                precipitation_sensor: true,
+            },
+        );
+        assert_eq!(remark.colourise(), "AO2".black().on_white());
+        let remark = Remark::new(
+            &["LTG", "DSNT", "NE"],
+            RemarkKind::Lightning {
+                frequency: None,
+                location: vec!["DSNT".to_string(), "NE".to_string()],
+            },
+        );
+        assert_eq!(
+            remark.colourise(),
+            "LTG DSNT NE".bold().bright_white().on_red()
+        );
+    }
+}
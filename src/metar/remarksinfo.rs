@@ -0,0 +1,225 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/remarksinfo.rs
+
+//! Values decoded from the remarks by `AvWx`, e.g. exact temperatures and pressure tendencies.
+
+use colored::{Color, ColoredString, Colorize};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(PartialEq, Debug, Default, Clone, Serialize)]
+/// Values decoded from the remarks section by `AvWx` (`remarks_info`). Temperatures are in °C, pressures in hPa, precipitation and snow depth in inches.
+pub struct RemarksInfo {
+    /// Pressure tendency during the last three hours.
+    pub pressure_tendency: Option<PressureTendency>,
+    /// Sea-level pressure.
+    pub sea_level_pressure: Option<f64>,
+    /// Temperature to a tenth of a degree.
+    pub temperature_decimal: Option<f64>,
+    /// Dewpoint to a tenth of a degree.
+    pub dewpoint_decimal: Option<f64>,
+    /// Maximum temperature during the last six hours.
+    pub maximum_temperature_6: Option<f64>,
+    /// Minimum temperature during the last six hours.
+    pub minimum_temperature_6: Option<f64>,
+    /// Maximum temperature during the last 24 hours.
+    pub maximum_temperature_24: Option<f64>,
+    /// Minimum temperature during the last 24 hours.
+    pub minimum_temperature_24: Option<f64>,
+    /// Precipitation during the last hour.
+    pub precip_hourly: Option<f64>,
+    /// Precipitation during the last 24 hours.
+    pub precip_24_hours: Option<f64>,
+    /// Precipitation during the last 36 hours.
+    pub precip_36_hours: Option<f64>,
+    /// Snow depth on the ground.
+    pub snow_depth: Option<f64>,
+    /// Duration of sunshine on the previous day.
+    pub sunshine_minutes: Option<i64>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+/// Characteristic and amount of the pressure change during the last three hours.
+pub struct PressureTendency {
+    /// Description of the characteristic, e.g. "Increasing, then steady".
+    pub tendency: String,
+    /// Pressure change in hPa.
+    pub change: f64,
+}
+
+impl RemarksInfo {
+    /// Reads the `remarks_info` object of a METAR in `AvWx` JSON form. Returns None if nothing was decoded.
+    #[must_use]
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let info = json.get("remarks_info")?;
+        let remarks = RemarksInfo {
+            pressure_tendency: get_pressure_tendency(info),
+            sea_level_pressure: get_number(info, "sea_level_pressure"),
+            temperature_decimal: get_number(info, "temperature_decimal"),
+            dewpoint_decimal: get_number(info, "dewpoint_decimal"),
+            maximum_temperature_6: get_number(info, "maximum_temperature_6"),
+            minimum_temperature_6: get_number(info, "minimum_temperature_6"),
+            maximum_temperature_24: get_number(info, "maximum_temperature_24"),
+            minimum_temperature_24: get_number(info, "minimum_temperature_24"),
+            precip_hourly: get_number(info, "precip_hourly"),
+            precip_24_hours: get_number(info, "precip_24_hours"),
+            precip_36_hours: get_number(info, "precip_36_hours"),
+            snow_depth: get_number(info, "snow_depth"),
+            sunshine_minutes: info
+                .get("sunshine_minutes")
+                .and_then(|x| x.get("value"))
+                .and_then(Value::as_i64),
+        };
+        if remarks == RemarksInfo::default() {
+            None
+        } else {
+            Some(remarks)
+        }
+    }
+
+    /// Renders the decoded values as a separate remarks section.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let mut items: Vec<ColoredString> = Vec::new();
+
+        if let Some(slp) = self.sea_level_pressure {
+            items.push(format!("SLP {slp:.1} hPa").white());
+        }
+        if let Some(tendency) = &self.pressure_tendency {
+            items.push(
+                format!(
+                    "3h pressure {:+.1} hPa ({})",
+                    tendency.change, tendency.tendency
+                )
+                .color(if tendency.change < 0.0 {
+                    Color::Yellow
+                } else {
+                    Color::White
+                }),
+            );
+        }
+        if let (Some(temp), Some(dewpoint)) = (self.temperature_decimal, self.dewpoint_decimal) {
+            items.push(format!("T {temp:.1}/{dewpoint:.1} °C").white());
+        }
+        for (label, max, min) in [
+            ("6h", self.maximum_temperature_6, self.minimum_temperature_6),
+            (
+                "24h",
+                self.maximum_temperature_24,
+                self.minimum_temperature_24,
+            ),
+        ] {
+            if max.is_some() || min.is_some() {
+                items.push(format!("{label} max/min {}/{} °C", fmt_opt(max), fmt_opt(min)).white());
+            }
+        }
+        for (label, amount) in [
+            ("1h", self.precip_hourly),
+            ("24h", self.precip_24_hours),
+            ("36h", self.precip_36_hours),
+        ] {
+            if let Some(amount) = amount {
+                items.push(format!("{label} precip {amount:.2} in").cyan());
+            }
+        }
+        if let Some(depth) = self.snow_depth {
+            items.push(format!("snow depth {depth} in").bright_blue());
+        }
+        if let Some(minutes) = self.sunshine_minutes {
+            items.push(format!("sunshine {minutes} min").yellow());
+        }
+
+        let mut output: ColoredString = "RMK".bold();
+        for item in items {
+            output = format!("{output} {item}").into();
+        }
+        output
+    }
+}
+
+fn fmt_opt(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{value:.1}"))
+}
+
+/// Reads the value of a number object ({"repr", "value", "spoken"}).
+fn get_number(info: &Value, key: &str) -> Option<f64> {
+    info.get(key)?.get("value")?.as_f64()
+}
+
+fn get_pressure_tendency(info: &Value) -> Option<PressureTendency> {
+    let tendency = info.get("pressure_tendency")?;
+    Some(PressureTendency {
+        tendency: tendency.get("tendency")?.as_str()?.to_string(),
+        change: tendency.get("change")?.as_f64()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_remarks_info_from_json() {
+        let json: Value =
+            Value::from_str(&std::fs::read_to_string("tests/testdata/apiary_pretty.json").unwrap())
+                .unwrap();
+        let actual = RemarksInfo::from_json(&json).unwrap();
+        assert_eq!(actual.sea_level_pressure, Some(1004.2));
+        assert_eq!(actual.temperature_decimal, Some(10.0));
+        assert_eq!(actual.dewpoint_decimal, Some(6.7));
+        assert!(actual.snow_depth.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remarks_info_from_json_empty() {
+        let json: Value = Value::from_str("{\"remarks_info\": {}}").unwrap();
+        assert!(RemarksInfo::from_json(&json).is_none());
+        let json: Value = Value::from_str("{}").unwrap();
+        assert!(RemarksInfo::from_json(&json).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remarks_info_from_json_tendency() {
+        let json: Value = Value::from_str(
+            "{\"remarks_info\": {\"pressure_tendency\": {\"repr\": \"58033\", \"tendency\": \"Decreasing\", \"change\": -3.3}, \"sunshine_minutes\": {\"repr\": \"98096\", \"value\": 96}, \"maximum_temperature_24\": {\"repr\": \"0122\", \"value\": 12.2}}}",
+        )
+        .unwrap();
+        let actual = RemarksInfo::from_json(&json).unwrap();
+        assert_eq!(
+            actual.pressure_tendency,
+            Some(PressureTendency {
+                tendency: "Decreasing".to_string(),
+                change: -3.3
+            })
+        );
+        assert_eq!(actual.sunshine_minutes, Some(96));
+        assert_eq!(actual.maximum_temperature_24, Some(12.2));
+    }
+
+    #[tokio::test]
+    async fn test_remarks_info_colourise() {
+        let info = RemarksInfo {
+            sea_level_pressure: Some(1004.2),
+            temperature_decimal: Some(10.0),
+            dewpoint_decimal: Some(6.7),
+            maximum_temperature_6: Some(12.3),
+            precip_hourly: Some(0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.colourise().clear().to_string(),
+            "RMK SLP 1004.2 hPa T 10.0/6.7 °C 6h max/min 12.3/- °C 1h precip 0.00 in"
+        );
+    }
+}
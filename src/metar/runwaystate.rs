@@ -0,0 +1,382 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/runwaystate.rs
+
+//! Runway state groups giving deposits, their extent and depth, and the braking action.
+
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use super::{WxField, build_regex};
+use anyhow::anyhow;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Type of deposit on a runway.
+pub enum RunwayDeposit {
+    /// Clear and dry (0).
+    ClearDry,
+    /// Damp (1).
+    Damp,
+    /// Wet or water patches (2).
+    Wet,
+    /// Rime or frost (3).
+    Frost,
+    /// Dry snow (4).
+    DrySnow,
+    /// Wet snow (5).
+    WetSnow,
+    /// Slush (6).
+    Slush,
+    /// Ice (7).
+    Ice,
+    /// Compacted or rolled snow (8).
+    CompactedSnow,
+    /// Frozen ruts or ridges (9).
+    FrozenRuts,
+    /// Contamination has ceased to exist (CLRD).
+    Cleared,
+    /// Not reported (/).
+    NotReported,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Share of the runway covered by the deposit.
+pub enum RunwayExtent {
+    /// 10% or less (1).
+    UpTo10,
+    /// 11% to 25% (2).
+    UpTo25,
+    /// 26% to 50% (5).
+    UpTo50,
+    /// 51% to 100% (9).
+    UpTo100,
+    /// Not reported (/).
+    NotReported,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Depth of the deposit.
+pub enum DepositDepth {
+    /// Depth in millimetres. Codes 92 to 98 are converted to their depth, 98 meaning 40cm or more.
+    Millimetres(i64),
+    /// Runway not operational due to snow, slush, ice, large drifts or runway clearance (99).
+    NotOperational,
+    /// Depth not measurable or not significant (//).
+    NotReported,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Braking action on a runway, either measured or estimated.
+pub enum BrakingAction {
+    /// Measured friction coefficient in hundredths (01 to 90).
+    Friction(i64),
+    /// Poor (91).
+    Poor,
+    /// Medium to poor (92).
+    MediumPoor,
+    /// Medium (93).
+    Medium,
+    /// Medium to good (94).
+    MediumGood,
+    /// Good (95).
+    Good,
+    /// Figures unreliable (99).
+    Unreliable,
+    /// Not reported (//).
+    NotReported,
+}
+
+impl BrakingAction {
+    /// Estimated braking action corresponding to a measured friction coefficient.
+    #[must_use]
+    pub fn estimate(&self) -> Self {
+        match self {
+            BrakingAction::Friction(coefficient) => match coefficient {
+                40.. => BrakingAction::Good,
+                36..=39 => BrakingAction::MediumGood,
+                30..=35 => BrakingAction::Medium,
+                26..=29 => BrakingAction::MediumPoor,
+                _ => BrakingAction::Poor,
+            },
+            _ => *self,
+        }
+    }
+}
+
+/// Runway state group like "R24/290155" or "R06/CLRD70".
+static RUNWAY_STATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"^R(?<runway>\d{2}[LCR]?)/((?<cleared>CLRD)|(?<deposit>[0-9/])(?<extent>[1259/])(?<depth>\d{2}|//))(?<braking>\d{2}|//)$",
+    )
+});
+
+/// From a runway state group such as "R24/290155" or "R06/CLRD70" parses a `WxField::RunwayState`.
+pub(crate) fn runway_state_from_str(repr: &str) -> Option<WxField> {
+    let matches = RUNWAY_STATE_REGEX.captures(repr)?;
+
+    let (deposit, extent, depth) = if matches.name("cleared").is_some() {
+        (
+            RunwayDeposit::Cleared,
+            RunwayExtent::NotReported,
+            DepositDepth::NotReported,
+        )
+    } else {
+        (
+            matches["deposit"].parse().ok()?,
+            matches["extent"].parse().ok()?,
+            matches["depth"].parse().ok()?,
+        )
+    };
+
+    Some(WxField::RunwayState {
+        runway: matches["runway"].to_string(),
+        deposit,
+        extent,
+        depth,
+        braking: matches["braking"].parse().ok()?,
+    })
+}
+
+/// Parses a METAR in JSON form and returns a `Vec` of `WxField::RunwayState`. `AvWx` does not decode runway state groups, so the raw report is searched instead.
+pub(crate) fn get_runway_state_from_json(json: &Value) -> Vec<WxField> {
+    let Some(raw) = json.get("raw").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    raw.split_whitespace()
+        .take_while(|group| *group != "RMK")
+        .filter_map(runway_state_from_str)
+        .collect()
+}
+
+impl FromStr for RunwayDeposit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self::ClearDry),
+            "1" => Ok(Self::Damp),
+            "2" => Ok(Self::Wet),
+            "3" => Ok(Self::Frost),
+            "4" => Ok(Self::DrySnow),
+            "5" => Ok(Self::WetSnow),
+            "6" => Ok(Self::Slush),
+            "7" => Ok(Self::Ice),
+            "8" => Ok(Self::CompactedSnow),
+            "9" => Ok(Self::FrozenRuts),
+            "CLRD" => Ok(Self::Cleared),
+            "/" => Ok(Self::NotReported),
+            _ => Err(anyhow!("Invalid runway deposit {s}.")),
+        }
+    }
+}
+
+impl Display for RunwayDeposit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            RunwayDeposit::ClearDry => "clear and dry",
+            RunwayDeposit::Damp => "damp",
+            RunwayDeposit::Wet => "wet",
+            RunwayDeposit::Frost => "rime or frost",
+            RunwayDeposit::DrySnow => "dry snow",
+            RunwayDeposit::WetSnow => "wet snow",
+            RunwayDeposit::Slush => "slush",
+            RunwayDeposit::Ice => "ice",
+            RunwayDeposit::CompactedSnow => "compacted snow",
+            RunwayDeposit::FrozenRuts => "frozen ruts",
+            RunwayDeposit::Cleared => "cleared",
+            RunwayDeposit::NotReported => "deposit not reported",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl FromStr for RunwayExtent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::UpTo10),
+            "2" => Ok(Self::UpTo25),
+            "5" => Ok(Self::UpTo50),
+            "9" => Ok(Self::UpTo100),
+            "/" => Ok(Self::NotReported),
+            _ => Err(anyhow!("Invalid runway contamination extent {s}.")),
+        }
+    }
+}
+
+impl Display for RunwayExtent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            RunwayExtent::UpTo10 => "up to 10%",
+            RunwayExtent::UpTo25 => "11-25%",
+            RunwayExtent::UpTo50 => "26-50%",
+            RunwayExtent::UpTo100 => "51-100%",
+            RunwayExtent::NotReported => "",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl FromStr for DepositDepth {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "//" {
+            return Ok(Self::NotReported);
+        }
+        let code: i64 = s.parse()?;
+        match code {
+            0..=90 => Ok(Self::Millimetres(code)),
+            92..=98 => Ok(Self::Millimetres((code - 90) * 50)),
+            99 => Ok(Self::NotOperational),
+            _ => Err(anyhow!("Invalid deposit depth {s}.")),
+        }
+    }
+}
+
+impl Display for DepositDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepositDepth::Millimetres(0) => write!(f, "less than 1mm"),
+            DepositDepth::Millimetres(depth) => write!(f, "{depth}mm"),
+            DepositDepth::NotOperational => write!(f, "runway not operational"),
+            DepositDepth::NotReported => Ok(()),
+        }
+    }
+}
+
+impl FromStr for BrakingAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "//" {
+            return Ok(Self::NotReported);
+        }
+        let code: i64 = s.parse()?;
+        match code {
+            1..=90 => Ok(Self::Friction(code)),
+            91 => Ok(Self::Poor),
+            92 => Ok(Self::MediumPoor),
+            93 => Ok(Self::Medium),
+            94 => Ok(Self::MediumGood),
+            95 => Ok(Self::Good),
+            99 => Ok(Self::Unreliable),
+            _ => Err(anyhow!("Invalid braking action {s}.")),
+        }
+    }
+}
+
+impl Display for BrakingAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrakingAction::Friction(coefficient) => write!(f, "friction 0.{coefficient:02}"),
+            BrakingAction::Poor => write!(f, "braking poor"),
+            BrakingAction::MediumPoor => write!(f, "braking medium/poor"),
+            BrakingAction::Medium => write!(f, "braking medium"),
+            BrakingAction::MediumGood => write!(f, "braking medium/good"),
+            BrakingAction::Good => write!(f, "braking good"),
+            BrakingAction::Unreliable => write!(f, "braking unreliable"),
+            BrakingAction::NotReported => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_runway_state_from_str() {
+        let expected = WxField::RunwayState {
+            runway: "24".to_string(),
+            deposit: RunwayDeposit::Wet,
+            extent: RunwayExtent::UpTo100,
+            depth: DepositDepth::Millimetres(1),
+            braking: BrakingAction::Friction(55),
+        };
+        assert_eq!(runway_state_from_str("R24/290155"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_runway_state_from_str_not_reported() {
+        let expected = WxField::RunwayState {
+            runway: "06L".to_string(),
+            deposit: RunwayDeposit::Ice,
+            extent: RunwayExtent::NotReported,
+            depth: DepositDepth::NotReported,
+            braking: BrakingAction::Poor,
+        };
+        assert_eq!(runway_state_from_str("R06L/7///91"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_runway_state_from_str_cleared() {
+        let actual = runway_state_from_str("R24/CLRD70");
+        assert!(actual.is_some_and(|state| matches!(
+            state,
+            WxField::RunwayState {
+                deposit: RunwayDeposit::Cleared,
+                braking: BrakingAction::Friction(70),
+                ..
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_runway_state_from_str_err() {
+        assert!(runway_state_from_str("R24/P1500U").is_none());
+        assert!(runway_state_from_str("R24/2301").is_none());
+        assert!(runway_state_from_str("R24/230196").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deposit_depth_from_str() {
+        assert_eq!(
+            "92".parse::<DepositDepth>().unwrap(),
+            DepositDepth::Millimetres(100)
+        );
+        assert_eq!(
+            "99".parse::<DepositDepth>().unwrap(),
+            DepositDepth::NotOperational
+        );
+        assert!("91".parse::<DepositDepth>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_braking_action_estimate() {
+        assert_eq!(BrakingAction::Friction(55).estimate(), BrakingAction::Good);
+        assert_eq!(
+            BrakingAction::Friction(30).estimate(),
+            BrakingAction::Medium
+        );
+        assert_eq!(BrakingAction::Friction(20).estimate(), BrakingAction::Poor);
+        assert_eq!(
+            BrakingAction::Unreliable.estimate(),
+            BrakingAction::Unreliable
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_runway_state_from_json() {
+        let json: Value = serde_json::from_str(
+            "{\"raw\": \"ENGM 101220Z 01005KT 9999 FEW030 M05/M08 Q1020 R01L/420542 R19R/CLRD//\"}",
+        )
+        .unwrap();
+        assert_eq!(get_runway_state_from_json(&json).len(), 2);
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/rvr.rs
+
+//! Runway visual range groups with their limits and tendencies.
+
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use super::units::DistanceUnit;
+use super::{WxField, build_regex};
+use anyhow::anyhow;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+/// A single runway visual range value.
+pub struct RvrValue {
+    /// Measured range in the unit of the report.
+    pub range: i64,
+    /// Set if the range is outside of what the instruments can measure.
+    pub limit: RvrLimit,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Used to specify that a runway visual range is beyond the measurable range.
+pub enum RvrLimit {
+    /// Exact value.
+    Exact,
+    /// Range is above the reported value (P).
+    Above,
+    /// Range is below the reported value (M).
+    Below,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Tendency of the runway visual range during the last ten minutes.
+pub enum RvrTendency {
+    /// No tendency reported.
+    None,
+    /// Upward (U).
+    Upward,
+    /// Downward (D).
+    Downward,
+    /// No distinct change (N).
+    NoChange,
+}
+
+/// Parses a METAR in JSON form and returns a `Vec` of `WxField::RunwayVisualRange` for every runway reported.
+pub(crate) fn get_rvr_from_json(json: &Value) -> Vec<WxField> {
+    let mut result: Vec<WxField> = Vec::new();
+    if let Some(rvrs) = json.get("runway_visibility").and_then(|x| x.as_array()) {
+        for rvr in rvrs {
+            if let Some(repr) = rvr.get("repr").and_then(|x| x.as_str())
+                && let Some(field) = rvr_from_str(repr)
+            {
+                result.push(field);
+            }
+        }
+    }
+    result
+}
+
+/// Runway visual range group like "R24/P1500U" or "R06L/1200V1800FT/D".
+static RVR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"^R(?<runway>\d{2}[LCR]?)/(?<limit>[PM]?)(?<range>\d{4})(V(?<varlimit>[PM]?)(?<var>\d{4}))?(?<unit>FT)?/?(?<tendency>[UDN]?)$",
+    )
+});
+
+/// From a METAR compliant runway visual range group such as "R24/P1500U" or "R06L/1200V1800FT/D" parses a `WxField::RunwayVisualRange`.
+pub(crate) fn rvr_from_str(repr: &str) -> Option<WxField> {
+    let matches = RVR_REGEX.captures(repr)?;
+
+    let visibility = RvrValue {
+        range: matches["range"].parse().ok()?,
+        limit: matches["limit"].parse().ok()?,
+    };
+    let variable = match matches.name("var") {
+        Some(var) => Some(RvrValue {
+            range: var.as_str().parse().ok()?,
+            limit: matches["varlimit"].parse().ok()?,
+        }),
+        None => None,
+    };
+    let unit = if matches.name("unit").is_some() {
+        DistanceUnit::Ft
+    } else {
+        DistanceUnit::M
+    };
+
+    Some(WxField::RunwayVisualRange {
+        runway: matches["runway"].to_string(),
+        visibility,
+        variable,
+        tendency: matches["tendency"].parse().ok()?,
+        unit,
+    })
+}
+
+impl FromStr for RvrLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" => Ok(Self::Exact),
+            "p" => Ok(Self::Above),
+            "m" => Ok(Self::Below),
+            _ => Err(anyhow!("Invalid RVR limit {s}.")),
+        }
+    }
+}
+
+impl Display for RvrLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            RvrLimit::Exact => "",
+            RvrLimit::Above => "P",
+            RvrLimit::Below => "M",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl FromStr for RvrTendency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" => Ok(Self::None),
+            "u" => Ok(Self::Upward),
+            "d" => Ok(Self::Downward),
+            "n" => Ok(Self::NoChange),
+            _ => Err(anyhow!("Invalid RVR tendency {s}.")),
+        }
+    }
+}
+
+impl Display for RvrTendency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            RvrTendency::None => "",
+            RvrTendency::Upward => "U",
+            RvrTendency::Downward => "D",
+            RvrTendency::NoChange => "N",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl Display for RvrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{:04}", self.limit, self.range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rvr_from_str() {
+        let expected = WxField::RunwayVisualRange {
+            runway: "24".to_string(),
+            visibility: RvrValue {
+                range: 1500,
+                limit: RvrLimit::Above,
+            },
+            variable: None,
+            tendency: RvrTendency::Upward,
+            unit: DistanceUnit::M,
+        };
+        assert_eq!(rvr_from_str("R24/P1500U"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_rvr_from_str_variable_ft() {
+        let expected = WxField::RunwayVisualRange {
+            runway: "06L".to_string(),
+            visibility: RvrValue {
+                range: 1200,
+                limit: RvrLimit::Exact,
+            },
+            variable: Some(RvrValue {
+                range: 1800,
+                limit: RvrLimit::Exact,
+            }),
+            tendency: RvrTendency::Downward,
+            unit: DistanceUnit::Ft,
+        };
+        assert_eq!(rvr_from_str("R06L/1200V1800FT/D"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_rvr_from_str_below() {
+        let actual = rvr_from_str("R24R/M0050");
+        assert!(actual.is_some_and(|rvr| matches!(
+            rvr,
+            WxField::RunwayVisualRange {
+                visibility: RvrValue {
+                    range: 50,
+                    limit: RvrLimit::Below
+                },
+                tendency: RvrTendency::None,
+                ..
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_rvr_from_str_err() {
+        assert!(rvr_from_str("R24/290155").is_none());
+        assert!(rvr_from_str("RA").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_limit_from_str_err() {
+        assert!(RvrLimit::from_str("X").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rvr_value_display() {
+        let value = RvrValue {
+            range: 550,
+            limit: RvrLimit::Below,
+        };
+        assert_eq!(value.to_string(), "M0550");
+    }
+
+    #[tokio::test]
+    async fn test_get_rvr() {
+        let json: Value = Value::from_str(
+            "{\"runway_visibility\":[{\"repr\": \"R24/0600N\"},{\"repr\": \"R06/P2000\"}]}",
+        )
+        .unwrap();
+        let actual = get_rvr_from_json(&json);
+        assert_eq!(actual.len(), 2);
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/trend.rs
+
+//! Trend forecasts appended to METARs (NOSIG, BECMG, TEMPO).
+
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use anyhow::anyhow;
+use colored::{ColoredString, Colorize};
+use regex::Regex;
+use serde::Serialize;
+
+use super::remarks::remarks_from_tokens;
+use super::{AssessedField, WxField, build_regex, field_from_str};
+use crate::Config;
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+/// A trend forecast appended to a METAR, describing the expected development during the next two hours.
+pub struct Trend {
+    /// Kind of change.
+    pub change: TrendType,
+    /// Time qualifiers, if any.
+    pub times: Vec<TrendTime>,
+    /// Forecast conditions.
+    pub fields: Vec<WxField>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Kind of change announced by a trend forecast.
+pub enum TrendType {
+    /// No significant change expected.
+    Nosig,
+    /// Conditions are expected to change permanently.
+    Becmg,
+    /// Conditions are expected to change temporarily.
+    Tempo,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Time qualifier of a trend forecast. Times are given as hhmm in UTC.
+pub enum TrendTime {
+    /// From (FM).
+    From(u32),
+    /// Until (TL).
+    Until(u32),
+    /// At (AT).
+    At(u32),
+}
+
+#[derive(Debug, Serialize)]
+/// A trend forecast with all fields assessed against the personal minima, for JSON output.
+pub struct TrendReport {
+    /// Kind of change.
+    pub change: TrendType,
+    /// Times the change applies to.
+    pub times: Vec<TrendTime>,
+    /// Forecast fields.
+    pub fields: Vec<AssessedField>,
+}
+
+impl Trend {
+    /// Assesses all fields against the personal minima and converts them to the display units, for JSON output.
+    #[must_use]
+    pub fn report(&self, config: &Config) -> TrendReport {
+        TrendReport {
+            change: self.change,
+            times: self.times.clone(),
+            fields: self
+                .fields
+                .iter()
+                .map(|field| AssessedField::new(field, config))
+                .collect(),
+        }
+    }
+
+    /// The trend, with each field coloured by its assessment.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let mut output: ColoredString = match self.change {
+            TrendType::Nosig => self.change.to_string().green(),
+            TrendType::Becmg | TrendType::Tempo => self.change.to_string().bold(),
+        };
+        for time in &self.times {
+            output = format!("{output} {}", time.to_string().white()).into();
+        }
+        for field in &self.fields {
+            output = format!("{output} {}", field.colourise(config)).into();
+        }
+        output
+    }
+}
+
+/// True if `repr` starts a trend forecast.
+pub(crate) fn is_trend_start(repr: &str) -> bool {
+    repr.parse::<TrendType>().is_ok()
+}
+
+/// Splits the groups following the observation part of a METAR into its trend forecasts and the remaining remarks. If the groups do not start with a trend keyword, they are all treated as remarks.
+pub(crate) fn trends_and_remarks(tokens: &[&str]) -> (Vec<Trend>, Option<WxField>) {
+    let rmk_index = tokens
+        .iter()
+        .position(|token| *token == "RMK")
+        .unwrap_or(tokens.len());
+    let (trend_tokens, remark_tokens) = match tokens.first() {
+        Some(first) if is_trend_start(first) => tokens.split_at(rmk_index),
+        _ => tokens.split_at(0),
+    };
+
+    let remarks = if remark_tokens.is_empty() {
+        None
+    } else {
+        Some(WxField::Remarks(remarks_from_tokens(remark_tokens)))
+    };
+
+    (trends_from_tokens(trend_tokens), remarks)
+}
+
+/// Parses the groups of a trend section. Every trend keyword starts a new `Trend`, groups that cannot be decoded are skipped.
+fn trends_from_tokens(tokens: &[&str]) -> Vec<Trend> {
+    let mut trends: Vec<Trend> = Vec::new();
+    for token in tokens {
+        if let Ok(change) = token.parse::<TrendType>() {
+            trends.push(Trend {
+                change,
+                times: Vec::new(),
+                fields: Vec::new(),
+            });
+        } else if let Some(trend) = trends.last_mut() {
+            if let Ok(time) = token.parse::<TrendTime>() {
+                trend.times.push(time);
+            } else if let Some(field) = field_from_str(token) {
+                trend.fields.push(field);
+            }
+        }
+    }
+    trends
+}
+
+impl FromStr for TrendType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nosig" => Ok(Self::Nosig),
+            "becmg" => Ok(Self::Becmg),
+            "tempo" => Ok(Self::Tempo),
+            _ => Err(anyhow!("Invalid trend type {s}.")),
+        }
+    }
+}
+
+impl Display for TrendType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            TrendType::Nosig => "NOSIG",
+            TrendType::Becmg => "BECMG",
+            TrendType::Tempo => "TEMPO",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+/// Time of a trend change like "FM1200", "TL1330" or "AT1400".
+static TREND_TIME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<qualifier>FM|TL|AT)(?<time>\d{4})$"));
+
+impl FromStr for TrendTime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let matches = TREND_TIME_REGEX
+            .captures(s)
+            .ok_or_else(|| anyhow!("Invalid trend time {s}."))?;
+        let time: u32 = matches["time"].parse()?;
+        match &matches["qualifier"] {
+            "FM" => Ok(Self::From(time)),
+            "TL" => Ok(Self::Until(time)),
+            _ => Ok(Self::At(time)),
+        }
+    }
+}
+
+impl Display for TrendTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrendTime::From(time) => write!(f, "FM{time:04}"),
+            TrendTime::Until(time) => write!(f, "TL{time:04}"),
+            TrendTime::At(time) => write!(f, "AT{time:04}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::visibility::visibility_from_str;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trends_and_remarks_nosig() {
+        let (trends, remarks) = trends_and_remarks(&["NOSIG"]);
+        let expected = vec![Trend {
+            change: TrendType::Nosig,
+            times: Vec::new(),
+            fields: Vec::new(),
+        }];
+        assert_eq!(trends, expected);
+        assert!(remarks.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trends_and_remarks_with_rmk() {
+        let (trends, remarks) = trends_and_remarks(&["NOSIG", "RMK", "FEW150", "BKN250"]);
+        assert_eq!(trends.len(), 1);
+        assert!(matches!(
+            remarks,
+            Some(WxField::Remarks(rmks)) if rmks.len() == 1 && rmks[0].repr == "FEW150 BKN250"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trends_and_remarks_no_trend() {
+        let (trends, remarks) = trends_and_remarks(&["RWY", "UNAVAILABLE"]);
+        assert!(trends.is_empty());
+        assert!(matches!(
+            remarks,
+            Some(WxField::Remarks(rmks)) if rmks[0].repr == "RWY UNAVAILABLE"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trends_from_tokens_multiple() {
+        let trends = trends_from_tokens(&[
+            "BECMG", "FM1000", "TL1100", "24015KT", "TEMPO", "0800", "FG",
+        ]);
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[0].change, TrendType::Becmg);
+        assert_eq!(
+            trends[0].times,
+            vec![TrendTime::From(1000), TrendTime::Until(1100)]
+        );
+        assert_eq!(trends[0].fields.len(), 1);
+        assert_eq!(trends[1].change, TrendType::Tempo);
+        assert!(trends[1].times.is_empty());
+        assert!(matches!(
+            trends[1].fields[0],
+            WxField::Visibility { distance, .. } if distance.value() == 800
+        ));
+        assert_eq!(trends[1].fields.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_trend_time_from_str() {
+        assert_eq!("AT1230".parse::<TrendTime>().unwrap(), TrendTime::At(1230));
+        assert!("XX1230".parse::<TrendTime>().is_err());
+        assert!("FM12".parse::<TrendTime>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trend_time_display() {
+        assert_eq!(TrendTime::Until(30).to_string(), "TL0030");
+    }
+
+    #[tokio::test]
+    async fn test_trend_colourise() {
+        let config = Config::default();
+        let trend = Trend {
+            change: TrendType::Tempo,
+            times: vec![TrendTime::From(1000)],
+            fields: vec![visibility_from_str("0800").unwrap()],
+        };
+        let actual = trend.colourise(&config);
+        assert_eq!(actual.clear().to_string(), "TEMPO FM1000 0800");
+    }
+}
@@ -10,18 +10,35 @@
 // limitations under the License.
 // WxFetch - metar/units.rs
 
+//! Physical quantities with units, their conversion and comparison across units.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::Serialize;
 use serde_json::Value;
 
-#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+use super::visibility::distance_from_str;
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, Serialize)]
+/// The units a report or the output uses for each kind of quantity.
 pub struct Units {
+    /// Unit of the altimeter setting.
     pub pressure: PressureUnit,
+    /// Unit of cloud bases and other heights.
     pub altitude: AltitudeUnit,
+    /// Unit of wind speeds.
     pub wind_speed: SpeedUnit,
+    /// Unit of temperatures and dewpoints.
     pub temperature: TemperatureUnit,
+    /// Unit of visibilities.
     pub distance: DistanceUnit,
 }
 
 impl Units {
+    /// Reads the units from the "units" object of an `AvWx` response, using the default for any that are missing.
+    #[must_use]
     pub fn from_json(json: &Value) -> Self {
         match json.get("units") {
             Some(units_json) => {
@@ -63,93 +80,757 @@ impl Units {
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Unit of an atmospheric pressure.
 pub enum PressureUnit {
+    /// Hectopascals.
     #[default]
     Hpa,
+    /// Inches of mercury.
     Inhg,
 }
 
+impl FromStr for PressureUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hpa" => Ok(Self::Hpa),
+            "inhg" => Ok(Self::Inhg),
+            _ => Err(anyhow!("Invalid pressure unit {s}.")),
+        }
+    }
+}
+
 impl From<&str> for PressureUnit {
+    /// Parses the unit, falling back to the default for unknown units.
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "hpa" => Self::Hpa,
-            "inhg" => Self::Inhg,
-            _ => Self::default(),
-        }
+        value.parse().unwrap_or_default()
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Unit of an altitude or height.
 pub enum AltitudeUnit {
+    /// Feet.
     #[default]
     Ft,
+    /// Metres.
     M,
 }
 
+impl FromStr for AltitudeUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ft" => Ok(Self::Ft),
+            "m" => Ok(Self::M),
+            _ => Err(anyhow!("Invalid altitude unit {s}.")),
+        }
+    }
+}
+
 impl From<&str> for AltitudeUnit {
+    /// Parses the unit, falling back to the default for unknown units.
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "ft" => Self::Ft,
-            "m" => Self::M,
-            _ => Self::default(),
-        }
+        value.parse().unwrap_or_default()
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Unit of a speed.
 pub enum SpeedUnit {
+    /// Knots.
     #[default]
     Kt,
+    /// Kilometres per hour.
     Kph,
+    /// Statute miles per hour.
     Mph,
+    /// Metres per second.
+    Mps,
+}
+
+impl FromStr for SpeedUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "kt" | "kts" => Ok(Self::Kt),
+            "kph" | "kmh" => Ok(Self::Kph),
+            "mph" => Ok(Self::Mph),
+            "mps" | "m/s" => Ok(Self::Mps),
+            _ => Err(anyhow!("Invalid speed unit {s}.")),
+        }
+    }
 }
 
 impl From<&str> for SpeedUnit {
+    /// Parses the unit, falling back to the default for unknown units.
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "kt" => Self::Kt,
-            "kph" => Self::Kph,
-            "mph" => Self::Mph,
-            _ => Self::default(),
-        }
+        value.parse().unwrap_or_default()
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Unit of a temperature.
 pub enum TemperatureUnit {
+    /// Degrees Celsius.
     #[default]
     C,
+    /// Degrees Fahrenheit.
     F,
 }
 
+impl FromStr for TemperatureUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "c" => Ok(Self::C),
+            "f" => Ok(Self::F),
+            _ => Err(anyhow!("Invalid temperature unit {s}.")),
+        }
+    }
+}
+
 impl From<&str> for TemperatureUnit {
+    /// Parses the unit, falling back to the default for unknown units.
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "c" => Self::C,
-            "f" => Self::F,
-            _ => Self::default(),
-        }
+        value.parse().unwrap_or_default()
     }
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Unit of a distance.
 pub enum DistanceUnit {
+    /// Metres.
     #[default]
     M,
+    /// Nautical miles.
     Nm,
+    /// Statute miles.
+    #[serde(rename = "sm")]
     Mi,
+    /// Kilometres.
     Km,
+    /// Feet.
+    Ft,
+}
+
+impl FromStr for DistanceUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "m" => Ok(Self::M),
+            "nm" => Ok(Self::Nm),
+            "mi" | "sm" => Ok(Self::Mi),
+            "km" => Ok(Self::Km),
+            "ft" => Ok(Self::Ft),
+            _ => Err(anyhow!("Invalid distance unit {s}.")),
+        }
+    }
 }
 
 impl From<&str> for DistanceUnit {
+    /// Parses the unit, falling back to the default for unknown units.
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "m" => Self::M,
-            "nm" => Self::Nm,
-            "mi" => Self::Mi,
-            "km" => Self::Km,
-            _ => Self::default(),
+        value.parse().unwrap_or_default()
+    }
+}
+
+/// Divides and rounds to the nearest integer, halves away from zero.
+fn div_round(numerator: i64, denominator: i64) -> i64 {
+    let quotient = numerator / denominator;
+    if 2 * (numerator % denominator).abs() >= denominator {
+        quotient + numerator.signum()
+    } else {
+        quotient
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Implements comparison for quantities by their unit-independent base value, so that e.g. 1 kt equals 1852 m/h.
+macro_rules! impl_quantity_ordering {
+    ($quantity:ty) => {
+        impl PartialEq for $quantity {
+            fn eq(&self, other: &Self) -> bool {
+                self.base == other.base
+            }
+        }
+
+        impl Eq for $quantity {}
+
+        impl PartialOrd for $quantity {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $quantity {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.base.cmp(&other.base)
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A speed. Converting between units only changes the unit it is displayed in, the value is kept exactly.
+pub struct Speed {
+    /// Speed in millimetres per hour.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: SpeedUnit,
+}
+
+impl SpeedUnit {
+    /// Millimetres per hour in one unit.
+    fn factor(self) -> i64 {
+        match self {
+            SpeedUnit::Kt => 1_852_000,
+            SpeedUnit::Kph => 1_000_000,
+            SpeedUnit::Mph => 1_609_344,
+            SpeedUnit::Mps => 3_600_000,
+        }
+    }
+}
+
+impl Speed {
+    /// A speed of `value` units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speed is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: SpeedUnit) -> Self {
+        Self::checked_new(value, unit).expect("Speed out of range.")
+    }
+
+    /// Like `new`, but returns None if the speed is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: SpeedUnit) -> Option<Self> {
+        Some(Speed {
+            base: value.checked_mul(unit.factor())?,
+            unit,
+        })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        div_round(self.base, self.unit.factor())
+    }
+
+    /// The same speed, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: SpeedUnit) -> Self {
+        Speed { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(Speed);
+
+impl std::ops::Sub for Speed {
+    type Output = Speed;
+
+    /// Difference of two speeds, in the unit of the first one.
+    fn sub(self, other: Speed) -> Speed {
+        Speed {
+            base: self.base.saturating_sub(other.base),
+            unit: self.unit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A distance. Statute miles are kept in sixteenths so values like 1 1/2 or 3/16 stay exact.
+pub struct Distance {
+    /// Distance in tenths of a millimetre.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: DistanceUnit,
+}
+
+impl DistanceUnit {
+    /// Tenths of a millimetre in one unit.
+    fn factor(self) -> i64 {
+        match self {
+            DistanceUnit::M => 10_000,
+            DistanceUnit::Km => 10_000_000,
+            DistanceUnit::Nm => 18_520_000,
+            DistanceUnit::Mi => 16_093_440,
+            DistanceUnit::Ft => 3_048,
+        }
+    }
+}
+
+impl Distance {
+    /// A distance of `value` units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distance is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: DistanceUnit) -> Self {
+        Self::checked_new(value, unit).expect("Distance out of range.")
+    }
+
+    /// Like `new`, but returns None if the distance is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: DistanceUnit) -> Option<Self> {
+        Some(Distance {
+            base: value.checked_mul(unit.factor())?,
+            unit,
+        })
+    }
+
+    /// A distance of `value / denominator` units, e.g. 3/16 SM. Returns None if the denominator is not positive or the value is out of range.
+    #[must_use]
+    pub fn fraction(value: i64, denominator: i64, unit: DistanceUnit) -> Option<Self> {
+        if denominator <= 0 {
+            return None;
+        }
+        Some(Distance {
+            base: div_round(value.checked_mul(unit.factor())?, denominator),
+            unit,
+        })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        div_round(self.base, self.unit.factor())
+    }
+
+    /// Value in the current unit as a reduced fraction (numerator, denominator). Statute miles are resolved to sixteenths, all other units to whole numbers.
+    #[must_use]
+    pub fn as_fraction(&self) -> (i64, i64) {
+        match self.unit {
+            DistanceUnit::Mi => {
+                let sixteenths = div_round(self.base, self.unit.factor() / 16);
+                let divisor = gcd(sixteenths, 16).max(1);
+                (sixteenths / divisor, 16 / divisor)
+            }
+            _ => (self.value(), 1),
+        }
+    }
+
+    /// The same distance, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: DistanceUnit) -> Self {
+        Distance { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(Distance);
+
+#[derive(Debug, Clone, Copy)]
+/// An altitude or height, e.g. of a cloud base.
+pub struct Altitude {
+    /// Altitude in tenths of a millimetre.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: AltitudeUnit,
+}
+
+impl AltitudeUnit {
+    /// Tenths of a millimetre in one unit.
+    fn factor(self) -> i64 {
+        match self {
+            AltitudeUnit::Ft => 3_048,
+            AltitudeUnit::M => 10_000,
+        }
+    }
+}
+
+impl Altitude {
+    /// An altitude of `value` units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the altitude is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: AltitudeUnit) -> Self {
+        Self::checked_new(value, unit).expect("Altitude out of range.")
+    }
+
+    /// Like `new`, but returns None if the altitude is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: AltitudeUnit) -> Option<Self> {
+        Some(Altitude {
+            base: value.checked_mul(unit.factor())?,
+            unit,
+        })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        div_round(self.base, self.unit.factor())
+    }
+
+    /// The same altitude, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: AltitudeUnit) -> Self {
+        Altitude { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(Altitude);
+
+#[derive(Debug, Clone, Copy)]
+/// An atmospheric pressure. Values in inches of mercury are given in hundredths, as in METARs (A2992).
+pub struct Pressure {
+    /// Pressure in millionths of a hectopascal.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: PressureUnit,
+}
+
+impl PressureUnit {
+    /// Millionths of a hectopascal in one unit (hundredths for inHg).
+    fn factor(self) -> i64 {
+        match self {
+            PressureUnit::Hpa => 1_000_000,
+            PressureUnit::Inhg => 338_639,
+        }
+    }
+}
+
+impl Pressure {
+    /// A pressure of `value` units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pressure is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: PressureUnit) -> Self {
+        Self::checked_new(value, unit).expect("Pressure out of range.")
+    }
+
+    /// Like `new`, but returns None if the pressure is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: PressureUnit) -> Option<Self> {
+        Some(Pressure {
+            base: value.checked_mul(unit.factor())?,
+            unit,
+        })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        div_round(self.base, self.unit.factor())
+    }
+
+    /// The same pressure, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: PressureUnit) -> Self {
+        Pressure { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(Pressure);
+
+#[derive(Debug, Clone, Copy)]
+/// A temperature.
+pub struct Temperature {
+    /// Temperature in fifths of a degree Fahrenheit, which makes both whole °C and whole °F exact.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: TemperatureUnit,
+}
+
+impl Temperature {
+    /// A temperature of `value` degrees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the temperature is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: TemperatureUnit) -> Self {
+        Self::checked_new(value, unit).expect("Temperature out of range.")
+    }
+
+    /// Like `new`, but returns None if the temperature is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: TemperatureUnit) -> Option<Self> {
+        let base = match unit {
+            TemperatureUnit::C => value.checked_mul(9)?.checked_add(160)?,
+            TemperatureUnit::F => value.checked_mul(5)?,
+        };
+        Some(Temperature { base, unit })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        match self.unit {
+            TemperatureUnit::C => div_round(self.base.saturating_sub(160), 9),
+            TemperatureUnit::F => div_round(self.base, 5),
+        }
+    }
+
+    /// Exact difference to another temperature in the current unit, e.g. the dewpoint spread.
+    #[must_use]
+    pub fn spread(&self, other: &Temperature) -> TemperatureDifference {
+        TemperatureDifference {
+            base: self.base.saturating_sub(other.base),
+            unit: self.unit,
+        }
+    }
+
+    /// The same temperature, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: TemperatureUnit) -> Self {
+        Temperature { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(Temperature);
+
+#[derive(Debug, Clone, Copy)]
+/// A difference between two temperatures, e.g. the spread between temperature and dewpoint. Unlike a temperature it is measured from zero in both units, so that 5 °C equal 9 °F.
+pub struct TemperatureDifference {
+    /// Difference in fifths of a degree Fahrenheit.
+    base: i64,
+    /// Unit the value is given and displayed in.
+    pub unit: TemperatureUnit,
+}
+
+impl TemperatureDifference {
+    /// A temperature difference of `value` degrees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the difference is out of range, see `checked_new`.
+    #[must_use]
+    pub fn new(value: i64, unit: TemperatureUnit) -> Self {
+        Self::checked_new(value, unit).expect("Temperature difference out of range.")
+    }
+
+    /// Like `new`, but returns None if the difference is out of range.
+    #[must_use]
+    pub fn checked_new(value: i64, unit: TemperatureUnit) -> Option<Self> {
+        let base = match unit {
+            TemperatureUnit::C => value.checked_mul(9)?,
+            TemperatureUnit::F => value.checked_mul(5)?,
+        };
+        Some(TemperatureDifference { base, unit })
+    }
+
+    /// Value in the current unit, rounded to a whole number.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        match self.unit {
+            TemperatureUnit::C => div_round(self.base, 9),
+            TemperatureUnit::F => div_round(self.base, 5),
+        }
+    }
+
+    /// The same difference, expressed in another unit.
+    #[must_use]
+    pub fn convert(self, unit: TemperatureUnit) -> Self {
+        TemperatureDifference { unit, ..self }
+    }
+}
+
+impl_quantity_ordering!(TemperatureDifference);
+
+/// Splits a quantity like "20 kt" or "1/2SM" into its value and unit.
+fn split_quantity(s: &str) -> Result<(&str, &str), anyhow::Error> {
+    let index = s
+        .find(char::is_alphabetic)
+        .ok_or_else(|| anyhow!("Missing unit in {s}."))?;
+    let (value, unit) = s.split_at(index);
+    Ok((value.trim(), unit.trim()))
+}
+
+impl FromStr for Speed {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_quantity(s)?;
+        Speed::checked_new(value.parse()?, unit.parse()?)
+            .ok_or_else(|| anyhow!("Speed {s} out of range."))
+    }
+}
+
+impl FromStr for Distance {
+    type Err = anyhow::Error;
+
+    /// Parses a distance, statute miles may be given as fractions, e.g. "1 1/2 SM".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_quantity(s)?;
+        distance_from_str(value, unit.parse()?).ok_or_else(|| anyhow!("Invalid distance {s}."))
+    }
+}
+
+impl FromStr for Altitude {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_quantity(s)?;
+        Altitude::checked_new(value.parse()?, unit.parse()?)
+            .ok_or_else(|| anyhow!("Altitude {s} out of range."))
+    }
+}
+
+impl FromStr for Temperature {
+    type Err = anyhow::Error;
+
+    /// Parses a temperature, the unit may be given with or without degree sign, e.g. "-5 C" or "23 °F".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_quantity(s)?;
+        Temperature::checked_new(value.trim_end_matches('°').trim().parse()?, unit.parse()?)
+            .ok_or_else(|| anyhow!("Temperature {s} out of range."))
+    }
+}
+
+/// Serialized form of a quantity, e.g. `{"value": 15, "unit": "kt"}`.
+#[derive(Serialize)]
+struct QuantityRepr<T, U> {
+    value: T,
+    unit: U,
+}
+
+impl Serialize for Speed {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuantityRepr {
+            value: self.value(),
+            unit: self.unit,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Distance {
+    /// Whole distances are serialized as integers, fractions of statute miles as decimals, e.g. 1.5 for 1 1/2 SM.
+    #[allow(clippy::cast_precision_loss)]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.as_fraction() {
+            (value, 1) => QuantityRepr {
+                value,
+                unit: self.unit,
+            }
+            .serialize(serializer),
+            (numerator, denominator) => QuantityRepr {
+                value: numerator as f64 / denominator as f64,
+                unit: self.unit,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Altitude {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuantityRepr {
+            value: self.value(),
+            unit: self.unit,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Pressure {
+    /// Hectopascals are serialized as integers, inches of mercury as decimals, e.g. 29.92 rather than 2992.
+    #[allow(clippy::cast_precision_loss)]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.unit {
+            PressureUnit::Hpa => QuantityRepr {
+                value: self.value(),
+                unit: self.unit,
+            }
+            .serialize(serializer),
+            PressureUnit::Inhg => QuantityRepr {
+                value: self.value() as f64 / 100.0,
+                unit: self.unit,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl FromStr for TemperatureDifference {
+    type Err = anyhow::Error;
+
+    /// Parses a temperature difference like "3 C" or "5 °F".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_quantity(s)?;
+        TemperatureDifference::checked_new(
+            value.trim_end_matches('°').trim().parse()?,
+            unit.parse()?,
+        )
+        .ok_or_else(|| anyhow!("Temperature difference {s} out of range."))
+    }
+}
+
+impl Serialize for TemperatureDifference {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuantityRepr {
+            value: self.value(),
+            unit: self.unit,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Temperature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuantityRepr {
+            value: self.value(),
+            unit: self.unit,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Display for SpeedUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            SpeedUnit::Kt => "KT",
+            SpeedUnit::Kph => "KMH",
+            SpeedUnit::Mph => "MPH",
+            SpeedUnit::Mps => "MPS",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl Display for DistanceUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            DistanceUnit::M => "M",
+            DistanceUnit::Nm => "NM",
+            DistanceUnit::Mi => "SM",
+            DistanceUnit::Km => "KM",
+            DistanceUnit::Ft => "FT",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl Display for Distance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (numerator, denominator) = self.as_fraction();
+        let whole = numerator / denominator;
+        let remainder = numerator % denominator;
+        match (whole, remainder) {
+            (_, 0) => write!(f, "{whole}"),
+            (0, _) => write!(f, "{remainder}/{denominator}"),
+            _ => write!(f, "{whole} {remainder}/{denominator}"),
         }
     }
 }
@@ -201,4 +882,230 @@ mod tests {
         let actual = Units::from_json(&json);
         assert_eq!(actual, expected);
     }
+
+    #[tokio::test]
+    async fn test_distance_display() {
+        assert_eq!(
+            Distance::fraction(3, 2, DistanceUnit::Mi)
+                .unwrap()
+                .to_string(),
+            "1 1/2"
+        );
+        assert_eq!(
+            Distance::fraction(1, 4, DistanceUnit::Mi)
+                .unwrap()
+                .to_string(),
+            "1/4"
+        );
+        assert_eq!(Distance::new(10, DistanceUnit::Mi).to_string(), "10");
+    }
+
+    #[tokio::test]
+    async fn test_quantities_from_str() {
+        assert_eq!(
+            Speed::from_str("20 kt").unwrap(),
+            Speed::new(20, SpeedUnit::Kt)
+        );
+        assert_eq!(
+            Speed::from_str("25MPH").unwrap(),
+            Speed::new(25, SpeedUnit::Mph)
+        );
+        assert_eq!(
+            Distance::from_str("3 SM").unwrap(),
+            Distance::new(3, DistanceUnit::Mi)
+        );
+        assert_eq!(
+            Distance::from_str("1 1/2 sm").unwrap(),
+            Distance::fraction(3, 2, DistanceUnit::Mi).unwrap()
+        );
+        assert_eq!(
+            Altitude::from_str("300 m").unwrap(),
+            Altitude::new(300, AltitudeUnit::M)
+        );
+        assert_eq!(
+            Temperature::from_str("-5 °C").unwrap(),
+            Temperature::new(-5, TemperatureUnit::C)
+        );
+        assert_eq!(
+            Temperature::from_str("32 F").unwrap(),
+            Temperature::new(0, TemperatureUnit::C)
+        );
+        assert!(Speed::from_str("20").is_err());
+        assert!(Speed::from_str("20 furlongs").is_err());
+        assert!(Distance::from_str("a SM").is_err());
+        assert!(Distance::from_str("1 1/0 SM").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quantities_out_of_range() {
+        assert!(Speed::checked_new(i64::MAX / 1_000, SpeedUnit::Kt).is_none());
+        assert!(Distance::checked_new(i64::MAX, DistanceUnit::M).is_none());
+        assert!(Temperature::checked_new(i64::MAX / 9, TemperatureUnit::C).is_none());
+        assert!(TemperatureDifference::checked_new(i64::MIN, TemperatureUnit::F).is_none());
+        assert!(Speed::from_str("9223372036854775807 kt").is_err());
+        assert!(Distance::from_str("9223372036854775807 m").is_err());
+        let large = Distance::checked_new(i64::MAX / 16_093_440, DistanceUnit::Mi).unwrap();
+        assert_eq!(large.as_fraction(), (i64::MAX / 16_093_440, 1));
+    }
+
+    #[tokio::test]
+    async fn test_quantities_serialize() {
+        let json = |value: serde_json::Result<Value>| value.unwrap().to_string();
+        assert_eq!(
+            json(serde_json::to_value(Speed::new(15, SpeedUnit::Kt))),
+            r#"{"unit":"kt","value":15}"#
+        );
+        assert_eq!(
+            json(serde_json::to_value(
+                Distance::fraction(3, 4, DistanceUnit::Mi).unwrap()
+            )),
+            r#"{"unit":"sm","value":0.75}"#
+        );
+        assert_eq!(
+            json(serde_json::to_value(Distance::new(9999, DistanceUnit::M))),
+            r#"{"unit":"m","value":9999}"#
+        );
+        assert_eq!(
+            json(serde_json::to_value(Pressure::new(1013, PressureUnit::Hpa))),
+            r#"{"unit":"hpa","value":1013}"#
+        );
+        assert_eq!(
+            json(serde_json::to_value(Pressure::new(
+                2992,
+                PressureUnit::Inhg
+            ))),
+            r#"{"unit":"inhg","value":29.92}"#
+        );
+        assert_eq!(
+            json(serde_json::to_value(Temperature::new(
+                -3,
+                TemperatureUnit::C
+            ))),
+            r#"{"unit":"c","value":-3}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_speed_sub() {
+        let spread = Speed::new(35, SpeedUnit::Mph) - Speed::new(20, SpeedUnit::Kt);
+        assert_eq!(spread.unit, SpeedUnit::Mph);
+        assert_eq!(spread.value(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_distance_in_metres() {
+        assert_eq!(
+            Distance::new(1200, DistanceUnit::M)
+                .convert(DistanceUnit::M)
+                .value(),
+            1200
+        );
+        assert_eq!(
+            Distance::fraction(1, 2, DistanceUnit::Mi)
+                .unwrap()
+                .convert(DistanceUnit::M)
+                .value(),
+            805
+        );
+        assert_eq!(
+            Distance::new(10, DistanceUnit::Km)
+                .convert(DistanceUnit::M)
+                .value(),
+            10000
+        );
+        assert_eq!(
+            Distance::new(1000, DistanceUnit::Ft)
+                .convert(DistanceUnit::M)
+                .value(),
+            305
+        );
+    }
+
+    #[tokio::test]
+    async fn test_distance_convert() {
+        let distance = Distance::new(5000, DistanceUnit::M).convert(DistanceUnit::Mi);
+        assert_eq!(distance.to_string(), "3 1/8");
+        assert_eq!(distance.convert(DistanceUnit::M).value(), 5000);
+        assert!(Distance::new(3, DistanceUnit::Mi) > Distance::new(4800, DistanceUnit::M));
+    }
+
+    #[tokio::test]
+    async fn test_distance_unit_sm() {
+        assert_eq!(DistanceUnit::from("sm"), DistanceUnit::Mi);
+    }
+
+    #[tokio::test]
+    async fn test_speed_convert() {
+        let speed = Speed::new(20, SpeedUnit::Kt);
+        assert_eq!(speed.convert(SpeedUnit::Kph).value(), 37);
+        assert_eq!(speed.convert(SpeedUnit::Mps).value(), 10);
+        assert_eq!(
+            speed.convert(SpeedUnit::Mph).convert(SpeedUnit::Kt).value(),
+            20
+        );
+        assert_eq!(
+            Speed::new(1000, SpeedUnit::Kt),
+            Speed::new(1852, SpeedUnit::Kph)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_speed_comparison() {
+        assert!(Speed::new(10, SpeedUnit::Mps) > Speed::new(19, SpeedUnit::Kt));
+        assert!(Speed::new(10, SpeedUnit::Mps) < Speed::new(20, SpeedUnit::Kt));
+        assert_eq!(
+            Speed::new(36, SpeedUnit::Kph),
+            Speed::new(10, SpeedUnit::Mps)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_altitude_convert() {
+        let altitude = Altitude::new(1000, AltitudeUnit::Ft);
+        assert_eq!(altitude.convert(AltitudeUnit::M).value(), 305);
+        assert!(altitude < Altitude::new(305, AltitudeUnit::M));
+    }
+
+    #[tokio::test]
+    async fn test_pressure_convert() {
+        let pressure = Pressure::new(2992, PressureUnit::Inhg);
+        assert_eq!(pressure.convert(PressureUnit::Hpa).value(), 1013);
+        assert_eq!(
+            Pressure::new(1013, PressureUnit::Hpa)
+                .convert(PressureUnit::Inhg)
+                .value(),
+            2991
+        );
+        assert!(pressure > Pressure::new(1013, PressureUnit::Hpa));
+    }
+
+    #[tokio::test]
+    async fn test_temperature_convert() {
+        let temp = Temperature::new(-5, TemperatureUnit::C);
+        assert_eq!(temp.convert(TemperatureUnit::F).value(), 23);
+        assert_eq!(
+            temp.convert(TemperatureUnit::F)
+                .convert(TemperatureUnit::C)
+                .value(),
+            -5
+        );
+        assert_eq!(
+            Temperature::new(32, TemperatureUnit::F),
+            Temperature::new(0, TemperatureUnit::C)
+        );
+        assert!(Temperature::new(33, TemperatureUnit::F) > Temperature::new(0, TemperatureUnit::C));
+    }
+
+    #[tokio::test]
+    async fn test_temperature_difference() {
+        let spread = Temperature::new(15, TemperatureUnit::C)
+            .spread(&Temperature::new(10, TemperatureUnit::C));
+        assert_eq!(spread, TemperatureDifference::new(9, TemperatureUnit::F));
+        assert_eq!(spread.convert(TemperatureUnit::F).value(), 9);
+        assert!(spread > TemperatureDifference::new(4, TemperatureUnit::C));
+        assert_eq!(
+            "9 °F".parse::<TemperatureDifference>().unwrap(),
+            TemperatureDifference::new(5, TemperatureUnit::C)
+        );
+    }
 }
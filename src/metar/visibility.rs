@@ -0,0 +1,343 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/visibility.rs
+
+//! Prevailing and directional visibility.
+
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use super::rvr::RvrLimit;
+use super::trend::is_trend_start;
+use super::units::{Distance, DistanceUnit, Units};
+use super::{WxField, build_regex};
+use anyhow::anyhow;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+/// Minimum visibility reported in addition to the prevailing visibility, along with the direction it was observed in.
+pub struct DirectionalVisibility {
+    /// The minimum visibility.
+    pub distance: Distance,
+    /// Direction from the station in which it was observed.
+    pub direction: CompassDirection,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// The eight principal compass directions.
+pub enum CompassDirection {
+    /// North.
+    N,
+    /// Northeast.
+    Ne,
+    /// East.
+    E,
+    /// Southeast.
+    Se,
+    /// South.
+    S,
+    /// Southwest.
+    Sw,
+    /// West.
+    W,
+    /// Northwest.
+    Nw,
+}
+
+/// Whole statute miles preceding a fraction, like the "1" of "1 1/2SM".
+static WHOLE_MILES_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^\d{1,2}$"));
+/// Fraction of statute miles following whole miles, like the "1/2SM" of "1 1/2SM".
+static FRACTION_MILES_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^\d/\d{1,2}SM$"));
+/// Prevailing visibility in metres, like "4000" or "9999NDV".
+static METRES_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^\d{4}(NDV)?$"));
+/// Directional minimum visibility following the prevailing one, like "1200NE".
+static DIRECTIONAL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^\d{4}(N|NE|E|SE|S|SW|W|NW)$"));
+/// Visibility in metres with an optional minimum, like "9999" or "4000 1200NE".
+static METRES_VISIBILITY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(r"^(?<metres>\d{4})(NDV)?( (?<minimum>\d{4})(?<direction>N|NE|E|SE|S|SW|W|NW))?$")
+});
+/// Visibility in statute miles or kilometres, like "10SM", "P6SM" or "1 1/2SM".
+static UNIT_VISIBILITY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<limit>[PM]?)(?<distance>[\d /]+)(?<unit>SM|KM)$"));
+/// Whole, fractional or mixed distance, like "10", "1/4" or "1 1/2".
+static DISTANCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"^((?<whole>\d+) (?<numerator>\d+)/(?<denominator>\d+)|(?<value>\d+)(/(?<divisor>\d+))?)$",
+    )
+});
+
+/// Merges visibility reports spanning two groups ("1 1/2SM", "4000 1200NE") into single tokens so they can be decoded like any other group.
+pub(crate) fn join_visibility_groups(groups: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for group in groups {
+        if let Some(previous) = result.last_mut()
+            && ((WHOLE_MILES_REGEX.is_match(previous) && FRACTION_MILES_REGEX.is_match(&group))
+                || (METRES_REGEX.is_match(previous) && DIRECTIONAL_REGEX.is_match(&group)))
+        {
+            previous.push(' ');
+            previous.push_str(&group);
+        } else {
+            result.push(group);
+        }
+    }
+    result
+}
+
+/// From a visibility report such as "9999", "4000 1200NE", "10SM", "1 1/2SM", "P6SM" or "M1/4SM" parses a `WxField::Visibility`.
+pub(crate) fn visibility_from_str(repr: &str) -> Option<WxField> {
+    if repr == "CAVOK" {
+        return Some(WxField::Cavok);
+    }
+    if let Some(matches) = METRES_VISIBILITY_REGEX.captures(repr) {
+        let minimum = match (matches.name("minimum"), matches.name("direction")) {
+            (Some(minimum), Some(direction)) => Some(DirectionalVisibility {
+                distance: Distance::new(minimum.as_str().parse().ok()?, DistanceUnit::M),
+                direction: direction.as_str().parse().ok()?,
+            }),
+            _ => None,
+        };
+        return Some(WxField::Visibility {
+            distance: Distance::new(matches["metres"].parse().ok()?, DistanceUnit::M),
+            limit: RvrLimit::Exact,
+            minimum,
+        });
+    }
+    let matches = UNIT_VISIBILITY_REGEX.captures(repr)?;
+    let unit = if &matches["unit"] == "KM" {
+        DistanceUnit::Km
+    } else {
+        DistanceUnit::Mi
+    };
+    Some(WxField::Visibility {
+        distance: distance_from_str(&matches["distance"], unit)?,
+        limit: matches["limit"].parse().ok()?,
+        minimum: None,
+    })
+}
+
+/// Parses a whole, fractional or mixed distance such as "10", "1/4" or "1 1/2".
+pub(crate) fn distance_from_str(repr: &str, unit: DistanceUnit) -> Option<Distance> {
+    let matches = DISTANCE_REGEX.captures(repr)?;
+    if let Some(whole) = matches.name("whole") {
+        let whole: i64 = whole.as_str().parse().ok()?;
+        let numerator: i64 = matches["numerator"].parse().ok()?;
+        let denominator: i64 = matches["denominator"].parse().ok()?;
+        let value = whole.checked_mul(denominator)?.checked_add(numerator)?;
+        return Distance::fraction(value, denominator, unit);
+    }
+    let value: i64 = matches["value"].parse().ok()?;
+    match matches.name("divisor") {
+        Some(divisor) => {
+            let divisor: i64 = divisor.as_str().parse().ok()?;
+            Distance::fraction(value, divisor, unit)
+        }
+        None => Distance::checked_new(value, unit),
+    }
+}
+
+/// Parses the visibility of a METAR in JSON form. The raw report is searched first, up to the trends and remarks, as `AvWx` does not decode directional minimum visibility; otherwise the decoded value is used.
+pub(crate) fn get_visibility_from_json(json: &Value, units: Units) -> Option<WxField> {
+    if let Some(raw) = json.get("raw").and_then(Value::as_str) {
+        let groups = join_visibility_groups(raw.split_whitespace().map(String::from).collect());
+        if let Some(field) = groups
+            .iter()
+            .take_while(|group| *group != "RMK" && !is_trend_start(group))
+            .find_map(|group| visibility_from_str(group))
+        {
+            return Some(field);
+        }
+    }
+
+    let visibility = json.get("visibility")?;
+    let repr = visibility
+        .get("repr")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if repr == "CAVOK" {
+        return Some(WxField::Cavok);
+    }
+    let (limit, distance) = match repr.chars().next() {
+        Some('P') => (RvrLimit::Above, &repr[1..]),
+        Some('M') => (RvrLimit::Below, &repr[1..]),
+        _ => (RvrLimit::Exact, repr),
+    };
+    let distance = distance_from_str(distance, units.distance).or_else(|| {
+        let value = visibility.get("value")?.as_i64()?;
+        Some(Distance::new(value, units.distance))
+    })?;
+    Some(WxField::Visibility {
+        distance,
+        limit,
+        minimum: None,
+    })
+}
+
+impl FromStr for CompassDirection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "n" => Ok(Self::N),
+            "ne" => Ok(Self::Ne),
+            "e" => Ok(Self::E),
+            "se" => Ok(Self::Se),
+            "s" => Ok(Self::S),
+            "sw" => Ok(Self::Sw),
+            "w" => Ok(Self::W),
+            "nw" => Ok(Self::Nw),
+            _ => Err(anyhow!("Invalid compass direction {s}.")),
+        }
+    }
+}
+
+impl Display for CompassDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr: &str = match self {
+            CompassDirection::N => "N",
+            CompassDirection::Ne => "NE",
+            CompassDirection::E => "E",
+            CompassDirection::Se => "SE",
+            CompassDirection::S => "S",
+            CompassDirection::Sw => "SW",
+            CompassDirection::W => "W",
+            CompassDirection::Nw => "NW",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(repr: &str) -> Vec<String> {
+        join_visibility_groups(repr.split_whitespace().map(String::from).collect())
+    }
+
+    #[tokio::test]
+    async fn test_join_visibility_groups() {
+        assert_eq!(
+            groups("27010KT 1 1/2SM BR"),
+            vec!["27010KT", "1 1/2SM", "BR"]
+        );
+        assert_eq!(groups("4000 1200NE FEW010"), vec!["4000 1200NE", "FEW010"]);
+        assert_eq!(groups("1200 1500"), vec!["1200", "1500"]);
+    }
+
+    #[tokio::test]
+    async fn test_visibility_from_str_directional() {
+        let expected = WxField::Visibility {
+            distance: Distance::new(4000, DistanceUnit::M),
+            limit: RvrLimit::Exact,
+            minimum: Some(DirectionalVisibility {
+                distance: Distance::new(1200, DistanceUnit::M),
+                direction: CompassDirection::Ne,
+            }),
+        };
+        assert_eq!(visibility_from_str("4000 1200NE"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_visibility_from_str_statute_miles() {
+        assert_eq!(
+            visibility_from_str("1 1/2SM"),
+            Some(WxField::Visibility {
+                distance: Distance::fraction(3, 2, DistanceUnit::Mi).unwrap(),
+                limit: RvrLimit::Exact,
+                minimum: None,
+            })
+        );
+        assert_eq!(
+            visibility_from_str("P6SM"),
+            Some(WxField::Visibility {
+                distance: Distance::new(6, DistanceUnit::Mi),
+                limit: RvrLimit::Above,
+                minimum: None,
+            })
+        );
+        assert_eq!(
+            visibility_from_str("M1/4SM"),
+            Some(WxField::Visibility {
+                distance: Distance::fraction(1, 4, DistanceUnit::Mi).unwrap(),
+                limit: RvrLimit::Below,
+                minimum: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_visibility_from_str_err() {
+        assert!(visibility_from_str("1200NE").is_none());
+        assert!(visibility_from_str("1/0SM").is_none());
+        assert!(visibility_from_str("1 1/0SM").is_none());
+        assert!(visibility_from_str("SM").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_distance_from_str() {
+        assert_eq!(
+            distance_from_str("3/16", DistanceUnit::Mi),
+            Distance::fraction(3, 16, DistanceUnit::Mi)
+        );
+        assert_eq!(
+            distance_from_str("2 3/4", DistanceUnit::Mi),
+            Distance::fraction(11, 4, DistanceUnit::Mi)
+        );
+        assert!(distance_from_str("1 1", DistanceUnit::Mi).is_none());
+        assert!(distance_from_str("1 1/0", DistanceUnit::Mi).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_visibility_from_json_us() {
+        let json: Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/testdata/apiary_pretty.json").unwrap(),
+        )
+        .unwrap();
+        let expected = WxField::Visibility {
+            distance: Distance::new(10, DistanceUnit::Mi),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        };
+        assert_eq!(
+            get_visibility_from_json(&json, Units::from_json(&json)),
+            Some(expected)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_visibility_from_json_repr() {
+        let json: Value = serde_json::from_str(
+            "{\"visibility\":{\"repr\":\"M1/4\",\"value\":0.25},\"units\":{\"visibility\":\"sm\"}}",
+        )
+        .unwrap();
+        let expected = WxField::Visibility {
+            distance: Distance::fraction(1, 4, DistanceUnit::Mi).unwrap(),
+            limit: RvrLimit::Below,
+            minimum: None,
+        };
+        assert_eq!(
+            get_visibility_from_json(&json, Units::from_json(&json)),
+            Some(expected)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_visibility_from_json_ignores_trends() {
+        let json: Value = serde_json::from_str(
+            "{\"raw\":\"EDDK 211450Z 27010KT FEW040 12/05 Q1013 TEMPO 3000 RA\",\"visibility\":null}",
+        )
+        .unwrap();
+        assert_eq!(get_visibility_from_json(&json, Units::default()), None);
+    }
+}
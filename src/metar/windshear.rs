@@ -0,0 +1,152 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - metar/windshear.rs
+
+//! Wind shear groups for single or all runways.
+
+use std::{fmt::Display, sync::LazyLock};
+
+use super::{WxField, build_regex};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Runways affected by reported wind shear.
+pub enum WindShearRunway {
+    /// All runways (WS ALL RWY).
+    All,
+    /// A single runway designator such as "24" or "06L".
+    Runway(String),
+}
+
+/// Merges multi-group wind shear reports ("WS R24", "WS ALL RWY") into single tokens so they can be decoded like any other group.
+pub(crate) fn join_wind_shear_groups<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut pending: Option<String> = None;
+    for token in tokens {
+        if let Some(group) = pending.take() {
+            let group = format!("{group} {token}");
+            if group == "WS ALL" {
+                pending = Some(group);
+            } else {
+                result.push(group);
+            }
+        } else if token == "WS" {
+            pending = Some(token.to_string());
+        } else {
+            result.push(token.to_string());
+        }
+    }
+    result.extend(pending);
+    result
+}
+
+/// Joined wind shear group like "WS R24" or "WS ALL RWY".
+static WIND_SHEAR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^WS (?<all>ALL RWY)?(R(WY)?(?<runway>\d{2}[LCR]?))?$"));
+
+/// From a wind shear report such as "WS R24", "WS RWY24" or "WS ALL RWY" parses a `WxField::WindShear`.
+pub(crate) fn wind_shear_from_str(repr: &str) -> Option<WxField> {
+    let matches = WIND_SHEAR_REGEX.captures(repr)?;
+    if matches.name("all").is_some() {
+        return Some(WxField::WindShear(WindShearRunway::All));
+    }
+    let runway = matches.name("runway")?.as_str().to_string();
+    Some(WxField::WindShear(WindShearRunway::Runway(runway)))
+}
+
+/// Parses a METAR in JSON form and returns a `Vec` of `WxField::WindShear`. `AvWx` does not decode wind shear, so the raw report is searched instead.
+pub(crate) fn get_wind_shear_from_json(json: &Value) -> Vec<WxField> {
+    let Some(raw) = json.get("raw").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    join_wind_shear_groups(raw.split_whitespace())
+        .iter()
+        .take_while(|group| *group != "RMK")
+        .filter_map(|group| wind_shear_from_str(group))
+        .collect()
+}
+
+impl Display for WindShearRunway {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindShearRunway::All => write!(f, "ALL RWY"),
+            WindShearRunway::Runway(runway) => write!(f, "R{runway}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_join_wind_shear_groups() {
+        let tokens = "9999 WS R24 WS ALL RWY Q1013".split_whitespace();
+        let expected = vec!["9999", "WS R24", "WS ALL RWY", "Q1013"];
+        assert_eq!(join_wind_shear_groups(tokens), expected);
+    }
+
+    #[tokio::test]
+    async fn test_join_wind_shear_groups_trailing() {
+        let tokens = "Q1013 WS".split_whitespace();
+        assert_eq!(join_wind_shear_groups(tokens), vec!["Q1013", "WS"]);
+    }
+
+    #[tokio::test]
+    async fn test_wind_shear_from_str() {
+        assert_eq!(
+            wind_shear_from_str("WS R24L"),
+            Some(WxField::WindShear(WindShearRunway::Runway(
+                "24L".to_string()
+            )))
+        );
+        assert_eq!(
+            wind_shear_from_str("WS RWY06"),
+            Some(WxField::WindShear(WindShearRunway::Runway(
+                "06".to_string()
+            )))
+        );
+        assert_eq!(
+            wind_shear_from_str("WS ALL RWY"),
+            Some(WxField::WindShear(WindShearRunway::All))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wind_shear_from_str_err() {
+        assert!(wind_shear_from_str("WS").is_none());
+        assert!(wind_shear_from_str("WS ALL").is_none());
+        assert!(wind_shear_from_str("R24").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_wind_shear_from_json() {
+        let json: Value = Value::from_str(
+            "{\"raw\": \"EDDF 101220Z 24025G40KT 9999 WS R25C Q1002 RMK WS R07L\"}",
+        )
+        .unwrap();
+        let expected = vec![WxField::WindShear(WindShearRunway::Runway(
+            "25C".to_string(),
+        ))];
+        assert_eq!(get_wind_shear_from_json(&json), expected);
+    }
+
+    #[tokio::test]
+    async fn test_wind_shear_runway_display() {
+        assert_eq!(WindShearRunway::All.to_string(), "ALL RWY");
+        assert_eq!(WindShearRunway::Runway("24".to_string()).to_string(), "R24");
+    }
+}
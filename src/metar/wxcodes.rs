@@ -10,15 +10,19 @@
 // limitations under the License.
 // WxFetch - metar/wxcodes.rs
 
-use super::WxField;
-use anyhow::{anyhow, Error};
+//! Present and recent weather codes with their intensity, proximity and descriptor.
+
+use super::{WxField, build_regex};
+use anyhow::{Error, anyhow};
 use regex::Regex;
+use serde::Serialize;
 use serde_json::Value;
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(PartialEq, Eq, Debug, EnumIter)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, EnumIter, Serialize)]
+#[serde(rename_all = "snake_case")]
 /// Standardised codes for weather phenomena.
 pub enum WxCode {
     /// Rain.
@@ -108,20 +112,25 @@ impl FromStr for WxCode {
             "po" => Ok(Self::Po),
             "sq" => Ok(Self::Sq),
             "ss" => Ok(Self::Ss),
-            _ => Err(anyhow!("Invalid weather code {}.", s)),
+            _ => Err(anyhow!("Invalid weather code {s}.")),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 /// Used to specify a weather phenomenon's intensity.
 pub enum WxCodeIntensity {
+    /// Moderate, no prefix.
     Moderate,
+    /// Light, "-" prefix.
     Light,
+    /// Heavy, "+" prefix.
     Heavy,
 }
 
-#[derive(PartialEq, Eq, Debug, EnumIter)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, EnumIter, Serialize)]
+#[serde(rename_all = "snake_case")]
 /// Used to specify a weather phenomenon's distance from reporting staion.
 pub enum WxCodeProximity {
     /// On station.
@@ -154,12 +163,13 @@ impl FromStr for WxCodeProximity {
             "" => Ok(Self::OnStation),
             "vc" => Ok(Self::Vicinity),
             "dsnt" => Ok(Self::Distant),
-            _ => Err(anyhow!("Invalid weather proximity code {}.", s)),
+            _ => Err(anyhow!("Invalid weather proximity code {s}.")),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug, EnumIter)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, EnumIter, Serialize)]
+#[serde(rename_all = "snake_case")]
 /// Used to further specify a weather phenomenon.
 pub enum WxCodeDescription {
     /// No description.
@@ -213,7 +223,7 @@ impl FromStr for WxCodeDescription {
             "mi" => Ok(Self::Mi),
             "pr" => Ok(Self::Pr),
             "sh" => Ok(Self::Sh),
-            _ => Err(anyhow!("Invalid weather code descriptor {}.", s)),
+            _ => Err(anyhow!("Invalid weather code descriptor {s}.")),
         }
     }
 }
@@ -306,33 +316,77 @@ impl Display for WxCodeDescription {
     }
 }
 
-pub(crate) fn wxcode_from_str(repr: &str) -> Option<WxField> {
-    let regex_pattern = format!(
-        r"(?<intensity>({})?)(?<descr>({})?)(?<code>{})(?<location>({})?)",
+/// Present weather group like "-SHRA" or "VCTS".
+static WXCODE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(&format!(
+        r"^(?<intensity>({})?)(?<prefix>({})?)(?<descr>({})?)(?<codes>({})*)(?<location>({})?)$",
         WxCodeIntensity::get_regex(),
+        WxCodeProximity::get_regex(),
         WxCodeDescription::get_regex(),
         WxCode::get_regex(),
         WxCodeProximity::get_regex()
-    );
-    let regex = Regex::new(&regex_pattern)
-        .expect("Creating RegEx pattern failed. This is likely a software bug, please report it.");
-    let matches = regex.captures(repr)?;
-    let code: WxCode = matches["code"].parse().ok()?;
+    ))
+});
+
+/// From a present weather group such as "-RA", "TSRAGR", "VCSH" or "NSW" parses a `WxField::WxCode` (or `WxField::Nsw`). A group may combine several phenomena, and a descriptor may stand on its own ("TS").
+pub(crate) fn wxcode_from_str(repr: &str) -> Option<WxField> {
+    if repr == "NSW" {
+        return Some(WxField::Nsw);
+    }
+    let matches = WXCODE_REGEX.captures(repr)?;
+    let codes = codes_from_str(&matches["codes"])?;
     let intensity: WxCodeIntensity = matches["intensity"].parse().ok()?;
     let descriptor: WxCodeDescription = matches["descr"].parse().ok()?;
-    let proximity: WxCodeProximity = matches["location"].parse().ok()?;
+    let proximity: WxCodeProximity = match (&matches["prefix"], &matches["location"]) {
+        ("", location) => location.parse().ok()?,
+        (prefix, "") => prefix.parse().ok()?,
+        _ => return None,
+    };
+    if codes.is_empty() && descriptor == WxCodeDescription::None {
+        return None;
+    }
+
+    Some(WxField::WxCode(codes, intensity, proximity, descriptor))
+}
+
+/// Splits a run of weather codes such as "RASNGS" into the individual phenomena.
+fn codes_from_str(repr: &str) -> Option<Vec<WxCode>> {
+    let chars: Vec<char> = repr.chars().collect();
+    chars
+        .chunks(2)
+        .map(|code| code.iter().collect::<String>().parse().ok())
+        .collect()
+}
+
+/// Recent weather group like "RETSRA" or "RESN".
+static RECENT_WXCODE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(&format!(
+        r"^RE(?<descr>({})?)(?<codes>({})*)$",
+        WxCodeDescription::get_regex(),
+        WxCode::get_regex()
+    ))
+});
+
+/// From a recent weather group such as "RETSRA" or "RESN" parses a `WxField::RecentWeather`. Recent weather carries neither intensity nor proximity, but like present weather may combine phenomena or consist of a descriptor only ("RETS").
+pub(crate) fn recent_wxcode_from_str(repr: &str) -> Option<WxField> {
+    let matches = RECENT_WXCODE_REGEX.captures(repr)?;
+    let codes = codes_from_str(&matches["codes"])?;
+    let descriptor: WxCodeDescription = matches["descr"].parse().ok()?;
+    if codes.is_empty() && descriptor == WxCodeDescription::None {
+        return None;
+    }
 
-    Some(WxField::WxCode(code, intensity, proximity, descriptor))
+    Some(WxField::RecentWeather(codes, descriptor))
 }
 
-pub fn get_wxcodes_from_json(json: &Value) -> Vec<WxField> {
+pub(crate) fn get_wxcodes_from_json(json: &Value) -> Vec<WxField> {
     let mut result: Vec<WxField> = Vec::new();
     if let Some(wxcodes) = json.get("wx_codes").and_then(|x| x.as_array()) {
         for code in wxcodes {
-            if let Some(repr) = code.get("repr").and_then(|x| x.as_str()) {
-                if let Some(field) = wxcode_from_str(repr) {
-                    result.push(field);
-                }
+            if let Some(repr) = code.get("repr").and_then(|x| x.as_str())
+                && let Some(field) = wxcode_from_str(repr).or_else(|| recent_wxcode_from_str(repr))
+            {
+                result.push(field);
             }
         }
     }
@@ -344,8 +398,10 @@ mod tests {
     use serde_json::Value;
     use std::str::FromStr;
 
-    use super::{get_wxcodes_from_json, WxCode, WxCodeIntensity};
-    use crate::metar::{wxcodes::WxCodeDescription, WxCodeProximity, WxField};
+    use super::{
+        WxCode, WxCodeIntensity, get_wxcodes_from_json, recent_wxcode_from_str, wxcode_from_str,
+    };
+    use crate::metar::{WxCodeProximity, WxField, wxcodes::WxCodeDescription};
 
     #[tokio::test]
     async fn test_get_regex() {
@@ -371,7 +427,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_wxcodes_one() {
         let expected: Vec<WxField> = vec![WxField::WxCode(
-            WxCode::Ra,
+            vec![WxCode::Ra],
             WxCodeIntensity::Light,
             WxCodeProximity::OnStation,
             crate::metar::WxCodeDescription::None,
@@ -381,6 +437,113 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[tokio::test]
+    async fn test_wxcode_from_str_multiple() {
+        assert_eq!(
+            wxcode_from_str("TSRAGR"),
+            Some(WxField::WxCode(
+                vec![WxCode::Ra, WxCode::Gr],
+                WxCodeIntensity::Moderate,
+                WxCodeProximity::OnStation,
+                WxCodeDescription::Ts
+            ))
+        );
+        assert_eq!(
+            wxcode_from_str("-RASN"),
+            Some(WxField::WxCode(
+                vec![WxCode::Ra, WxCode::Sn],
+                WxCodeIntensity::Light,
+                WxCodeProximity::OnStation,
+                WxCodeDescription::None
+            ))
+        );
+        assert_eq!(
+            wxcode_from_str("+SHRASNGS"),
+            Some(WxField::WxCode(
+                vec![WxCode::Ra, WxCode::Sn, WxCode::Gs],
+                WxCodeIntensity::Heavy,
+                WxCodeProximity::OnStation,
+                WxCodeDescription::Sh
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wxcode_from_str_descriptor_only() {
+        assert_eq!(
+            wxcode_from_str("TS"),
+            Some(WxField::WxCode(
+                Vec::new(),
+                WxCodeIntensity::Moderate,
+                WxCodeProximity::OnStation,
+                WxCodeDescription::Ts
+            ))
+        );
+        assert_eq!(
+            wxcode_from_str("VCSH"),
+            Some(WxField::WxCode(
+                Vec::new(),
+                WxCodeIntensity::Moderate,
+                WxCodeProximity::Vicinity,
+                WxCodeDescription::Sh
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wxcode_from_str_nsw() {
+        assert_eq!(wxcode_from_str("NSW"), Some(WxField::Nsw));
+    }
+
+    #[tokio::test]
+    async fn test_wxcode_from_str_err() {
+        assert!(wxcode_from_str("").is_none());
+        assert!(wxcode_from_str("-").is_none());
+        assert!(wxcode_from_str("RAX").is_none());
+        assert!(wxcode_from_str("VCFGVC").is_none());
+        assert!(wxcode_from_str("FEW020").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recent_wxcode_from_str() {
+        assert_eq!(
+            recent_wxcode_from_str("RETSRA"),
+            Some(WxField::RecentWeather(
+                vec![WxCode::Ra],
+                WxCodeDescription::Ts
+            ))
+        );
+        assert_eq!(
+            recent_wxcode_from_str("RETS"),
+            Some(WxField::RecentWeather(Vec::new(), WxCodeDescription::Ts))
+        );
+        assert_eq!(
+            recent_wxcode_from_str("RESN"),
+            Some(WxField::RecentWeather(
+                vec![WxCode::Sn],
+                WxCodeDescription::None
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_wxcode_from_str_err() {
+        assert!(recent_wxcode_from_str("RE").is_none());
+        assert!(recent_wxcode_from_str("-RA").is_none());
+        assert!(recent_wxcode_from_str("RE+RA").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_wxcodes_recent() {
+        let json: Value =
+            Value::from_str("{\"wx_codes\":[{\"repr\":\"BR\"},{\"repr\":\"RESHRA\"}]}").unwrap();
+        let actual = get_wxcodes_from_json(&json);
+        assert_eq!(
+            actual[1],
+            WxField::RecentWeather(vec![WxCode::Ra], WxCodeDescription::Sh)
+        );
+    }
+
     #[tokio::test]
     async fn test_wxcode_from_str_ra() {
         let expected = WxCode::Ra;
@@ -0,0 +1,528 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - notam.rs
+
+//! Decoding of NOTAMs as returned by `AvWx`, highlighting closures and outages.
+
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use colored::{Color, ColoredString, Colorize};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::advisory::{format_level, json_reports};
+use crate::metar::units::{Altitude, AltitudeUnit};
+use crate::metar::{Assessment, build_regex};
+use crate::position::LatLong;
+
+/// Start of an item like "E)".
+static ITEM_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"(?:^|\s)(?<item>[QA-G])\)"));
+/// NOTAM number like "A1234/24".
+static ID_REGEX: LazyLock<Regex> = LazyLock::new(|| build_regex(r"^[A-Z]\d{4}/\d{2}$"));
+/// Centre and radius of the area of influence like "5052N00709E005".
+static AREA_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"^(?<lat>\d{2})(?<latmin>\d{2})(?<ns>[NS])(?<lon>\d{3})(?<lonmin>\d{2})(?<ew>[EW])(?<radius>\d{3})?$",
+    )
+});
+static AERODROME_CLOSURE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"\b(AD|AERODROME|AIRPORT) (CLSD|CLOSED)\b"));
+static RUNWAY_CLOSURE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"\bRWY \d{2}[LRC]?(/\d{2}[LRC]?)? (CLSD|CLOSED)\b"));
+static NAVAID_OUTAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(
+        r"\b(VOR|DME|NDB|TACAN|VORTAC|ILS|LOC|LLZ|GP)\b.*\b(U/S|UNSERVICEABLE|OUT OF SERVICE|OTS)",
+    )
+});
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+/// A notice to airmen (NOTAM) in the ICAO format.
+pub struct Notam {
+    /// Series, number and year, e.g. "A1234/24".
+    pub id: Option<String>,
+    /// New, replacing or cancelling NOTAM.
+    pub kind: NotamKind,
+    /// Decoded Q-line.
+    pub qualifiers: Option<Qualifiers>,
+    /// ICAO code of the affected location (A).
+    pub location: Option<String>,
+    /// Start of the validity (B).
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the validity (C), None if permanent or not given.
+    pub valid_to: Option<DateTime<Utc>>,
+    /// True if the end of the validity is estimated (EST).
+    pub estimated: bool,
+    /// True if the NOTAM is permanent (PERM).
+    pub permanent: bool,
+    /// Schedule within the validity (D), e.g. "DAILY 0600-1800".
+    pub schedule: Option<String>,
+    /// Plain language text (E).
+    pub text: String,
+    /// Critical aerodrome status reported by the NOTAM, if any.
+    pub highlight: Option<Highlight>,
+    /// Raw text of the NOTAM.
+    pub raw: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Kind of NOTAM as given after its number.
+pub enum NotamKind {
+    /// New NOTAM (NOTAMN).
+    New,
+    /// Replaces an earlier NOTAM (NOTAMR).
+    Replace,
+    /// Cancels an earlier NOTAM (NOTAMC).
+    Cancel,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+/// The Q-line of a NOTAM, e.g. "EDGG/QMRLC/IV/NBO/A/000/999/5052N00709E005".
+pub struct Qualifiers {
+    /// Flight information region.
+    pub fir: String,
+    /// NOTAM code, e.g. "QMRLC" for a runway closure.
+    pub code: String,
+    /// Affected traffic, I for IFR, V for VFR.
+    pub traffic: String,
+    /// Purpose, e.g. "NBO".
+    pub purpose: String,
+    /// Scope, A for aerodrome, E for en-route, W for navigation warning.
+    pub scope: String,
+    /// Lower limit.
+    pub lower: Option<Altitude>,
+    /// Upper limit.
+    pub upper: Option<Altitude>,
+    /// Centre of the area of influence.
+    pub center: Option<LatLong>,
+    /// Radius of the area of influence in nautical miles.
+    pub radius: Option<i64>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Critical aerodrome status to point out in a briefing.
+pub enum Highlight {
+    /// A runway is closed.
+    RunwayClosure,
+    /// The aerodrome is closed.
+    AerodromeClosure,
+    /// A radio navigation aid or landing system is out of service.
+    NavaidOutage,
+}
+
+impl Notam {
+    /// Parses a NOTAM as returned by `AvWx` from its raw text.
+    #[must_use]
+    pub fn from_json(json: &Value) -> Option<Self> {
+        Self::from_raw(json.get("raw")?.as_str()?)
+    }
+
+    /// Parses a NOTAM in the ICAO format, e.g. "A1234/24 NOTAMN Q) EDGG/QMRLC/IV/NBO/A/000/999/5052N00709E005 A) EDDK B) 2406210600 C) 2406211800 E) RWY 14L/32R CLSD". Reports without items are kept as text only. Returns None for empty input.
+    #[must_use]
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let items: Vec<(&str, usize, usize)> = ITEM_REGEX
+            .captures_iter(raw)
+            .filter_map(|captures| {
+                let item = captures.name("item")?;
+                Some((item.as_str(), captures.get(0)?.start(), item.end() + 1))
+            })
+            .collect();
+        let header = items.first().map_or(raw, |(_, start, _)| &raw[..*start]);
+        let item = |name: &str| {
+            items
+                .iter()
+                .enumerate()
+                .find(|(_, (item, _, _))| *item == name)
+                .map(|(index, (_, _, content_start))| {
+                    let end = items
+                        .get(index + 1)
+                        .map_or(raw.len(), |(_, start, _)| *start);
+                    raw[*content_start..end].trim()
+                })
+        };
+
+        let valid_to = item("C").unwrap_or_default();
+        let text = item("E").unwrap_or(if items.is_empty() { raw } else { "" });
+        let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let qualifiers = item("Q").and_then(qualifiers_from_str);
+        let highlight = qualifiers
+            .as_ref()
+            .and_then(|qualifiers| highlight_from_code(&qualifiers.code))
+            .or_else(|| highlight_from_text(&text));
+
+        Some(Notam {
+            id: header
+                .split_whitespace()
+                .find(|word| is_notam_id(word))
+                .map(ToString::to_string),
+            kind: kind_from_header(header),
+            qualifiers,
+            location: item("A").map(ToString::to_string),
+            valid_from: item("B").and_then(time_from_str),
+            valid_to: valid_to.split_whitespace().next().and_then(time_from_str),
+            estimated: valid_to.contains("EST"),
+            permanent: valid_to.contains("PERM"),
+            schedule: item("D").map(ToString::to_string),
+            text,
+            highlight,
+            raw: raw.to_string(),
+        })
+    }
+
+    /// True unless the NOTAM cancels another one or has expired at `time`. NOTAMs starting later are in force, so that upcoming closures are shown.
+    #[must_use]
+    pub fn is_in_force_at(&self, time: DateTime<Utc>) -> bool {
+        self.kind != NotamKind::Cancel && self.valid_to.is_none_or(|valid_to| time < valid_to)
+    }
+
+    /// Closures are rated bad, navaid outages marginal and all other NOTAMs good.
+    #[must_use]
+    pub fn assess(&self) -> Assessment {
+        match self.highlight {
+            Some(Highlight::RunwayClosure | Highlight::AerodromeClosure) => Assessment::Bad,
+            Some(Highlight::NavaidOutage) => Assessment::Marginal,
+            None => Assessment::Good,
+        }
+    }
+
+    /// Number, location, validity and text of the NOTAM, with the text coloured by its assessment.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let mut output: ColoredString = self.id.clone().unwrap_or_default().bold();
+        if let Some(location) = &self.location {
+            output = format!("{output} {location}").into();
+        }
+        if self.valid_from.is_some() || self.valid_to.is_some() || self.permanent {
+            output = format!("{output} {}", self.format_validity().white()).into();
+        }
+        let colour = Color::from(self.assess());
+        if let Some(highlight) = self.highlight {
+            let tag = match colour {
+                Color::Red => highlight.to_string().bold().bright_white().on_red(),
+                _ => highlight.to_string().bold().black().on_yellow(),
+            };
+            output = format!("{output} {tag} {}", self.text.color(colour)).into();
+        } else {
+            output = format!("{output} {}", self.text).into();
+        }
+        output.trim().to_string().into()
+    }
+
+    /// Formats the validity, e.g. "210600Z-211800Z EST" or "210600Z-PERM".
+    fn format_validity(&self) -> String {
+        let format_time = |time: Option<DateTime<Utc>>| {
+            time.map_or("?".to_string(), |time| time.format("%d%H%MZ").to_string())
+        };
+        let end = if self.permanent {
+            "PERM".to_string()
+        } else if self.estimated {
+            format!("{} EST", format_time(self.valid_to))
+        } else {
+            format_time(self.valid_to)
+        };
+        format!("{}-{end}", format_time(self.valid_from))
+    }
+}
+
+/// Decodes the NOTAMs of an `AvWx` response and returns those in force at `time`, closures and outages first.
+///
+/// # Errors
+///
+/// Returns an error if any report is not a NOTAM, e.g. an error message, so that a failed request is never mistaken for the absence of NOTAMs.
+pub fn notams_from_json(json: &Value, time: DateTime<Utc>) -> anyhow::Result<Vec<Notam>> {
+    let mut notams = json_reports(json)
+        .into_iter()
+        .map(|report| {
+            Notam::from_json(report).ok_or_else(|| anyhow!("Invalid NOTAM data received: {report}"))
+        })
+        .collect::<anyhow::Result<Vec<Notam>>>()?;
+    notams.retain(|notam| notam.is_in_force_at(time));
+    notams.sort_by_key(|notam| Reverse(notam.assess()));
+    Ok(notams)
+}
+
+impl Display for Highlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr = match self {
+            Highlight::RunwayClosure => "RWY CLSD",
+            Highlight::AerodromeClosure => "AD CLSD",
+            Highlight::NavaidOutage => "NAVAID U/S",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+impl Display for Qualifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}-{}",
+            self.fir,
+            self.code,
+            self.lower.map(format_level).unwrap_or_default(),
+            self.upper.map(format_level).unwrap_or_default()
+        )
+    }
+}
+
+/// Checks for a NOTAM number like "A1234/24".
+fn is_notam_id(word: &str) -> bool {
+    ID_REGEX.is_match(word)
+}
+
+fn kind_from_header(header: &str) -> NotamKind {
+    if header.contains("NOTAMR") {
+        NotamKind::Replace
+    } else if header.contains("NOTAMC") {
+        NotamKind::Cancel
+    } else {
+        NotamKind::New
+    }
+}
+
+/// Parses the content of a Q-line. Returns None if any of its eight parts is missing.
+fn qualifiers_from_str(repr: &str) -> Option<Qualifiers> {
+    let parts: Vec<&str> = repr.split('/').map(str::trim).collect();
+    let [fir, code, traffic, purpose, scope, lower, upper, area] = parts.as_slice() else {
+        return None;
+    };
+    let level = |repr: &str| {
+        repr.parse::<i64>()
+            .ok()
+            .map(|hectofeet| Altitude::new(hectofeet * 100, AltitudeUnit::Ft))
+    };
+    let captures = AREA_REGEX.captures(area);
+    let center = captures.as_ref().and_then(|captures| {
+        let degrees = |degrees: &str, minutes: &str| {
+            Some(
+                captures[degrees].parse::<f64>().ok()?
+                    + captures[minutes].parse::<f64>().ok()? / 60.0,
+            )
+        };
+        let lat = degrees("lat", "latmin")?;
+        let lon = degrees("lon", "lonmin")?;
+        Some(LatLong(
+            if &captures["ns"] == "S" { -lat } else { lat },
+            if &captures["ew"] == "W" { -lon } else { lon },
+        ))
+    });
+
+    Some(Qualifiers {
+        fir: (*fir).to_string(),
+        code: (*code).to_string(),
+        traffic: (*traffic).to_string(),
+        purpose: (*purpose).to_string(),
+        scope: (*scope).to_string(),
+        lower: level(lower),
+        upper: level(upper),
+        center,
+        radius: captures
+            .as_ref()
+            .and_then(|captures| captures.name("radius"))
+            .and_then(|radius| radius.as_str().parse().ok()),
+    })
+}
+
+/// Classifies a NOTAM code. The second and third letters give the subject, e.g. MR for runway, FA for aerodrome, N for navaids and I for instrument landing systems; the last two letters the condition, e.g. LC for closed, AS for unserviceable or AU for not available.
+fn highlight_from_code(code: &str) -> Option<Highlight> {
+    let subject = code.get(1..3)?;
+    let condition = code.get(3..5)?;
+    match (subject, condition) {
+        ("MR", "LC") => Some(Highlight::RunwayClosure),
+        ("FA", "LC") => Some(Highlight::AerodromeClosure),
+        (subject, "AS" | "AU" | "LC") if subject.starts_with(['N', 'I']) => {
+            Some(Highlight::NavaidOutage)
+        }
+        _ => None,
+    }
+}
+
+/// Classifies a NOTAM by its plain language text, for NOTAMs without a specific code.
+fn highlight_from_text(text: &str) -> Option<Highlight> {
+    if AERODROME_CLOSURE_REGEX.is_match(text) {
+        Some(Highlight::AerodromeClosure)
+    } else if RUNWAY_CLOSURE_REGEX.is_match(text) {
+        Some(Highlight::RunwayClosure)
+    } else if NAVAID_OUTAGE_REGEX.is_match(text) {
+        Some(Highlight::NavaidOutage)
+    } else {
+        None
+    }
+}
+
+/// Parses a date and time like "2406210600".
+fn time_from_str(repr: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(repr, "%y%m%d%H%M")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const RAW: &str = "A1234/24 NOTAMN\nQ) EDGG/QMRLC/IV/NBO/A/000/999/5052N00709E005\nA) EDDK B) 2406210600 C) 2406211800 EST\nD) DAILY 0600-1800\nE) RWY 14L/32R CLSD\nDUE TO WIP";
+
+    #[tokio::test]
+    async fn test_notam_from_raw() {
+        let notam = Notam::from_raw(RAW).unwrap();
+        assert_eq!(notam.id, Some("A1234/24".to_string()));
+        assert_eq!(notam.kind, NotamKind::New);
+        assert_eq!(notam.location, Some("EDDK".to_string()));
+        assert_eq!(
+            notam.valid_from,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 6, 0, 0).unwrap())
+        );
+        assert_eq!(
+            notam.valid_to,
+            Some(Utc.with_ymd_and_hms(2024, 6, 21, 18, 0, 0).unwrap())
+        );
+        assert!(notam.estimated);
+        assert!(!notam.permanent);
+        assert_eq!(notam.schedule, Some("DAILY 0600-1800".to_string()));
+        assert_eq!(notam.text, "RWY 14L/32R CLSD DUE TO WIP");
+        assert_eq!(notam.highlight, Some(Highlight::RunwayClosure));
+        assert_eq!(notam.assess(), Assessment::Bad);
+    }
+
+    #[tokio::test]
+    async fn test_qualifiers_from_str() {
+        let qualifiers = qualifiers_from_str("EDGG/QMRLC/IV/NBO/A/000/999/5052N00709E005").unwrap();
+        assert_eq!(qualifiers.fir, "EDGG");
+        assert_eq!(qualifiers.code, "QMRLC");
+        assert_eq!(qualifiers.traffic, "IV");
+        assert_eq!(qualifiers.purpose, "NBO");
+        assert_eq!(qualifiers.scope, "A");
+        assert_eq!(qualifiers.lower, Some(Altitude::new(0, AltitudeUnit::Ft)));
+        assert_eq!(
+            qualifiers.upper,
+            Some(Altitude::new(99900, AltitudeUnit::Ft))
+        );
+        let LatLong(lat, lon) = qualifiers.center.clone().unwrap();
+        assert!((lat - 50.8667).abs() < 0.001);
+        assert!((lon - 7.15).abs() < 0.001);
+        assert_eq!(qualifiers.radius, Some(5));
+        assert_eq!(qualifiers.to_string(), "EDGG QMRLC SFC-FL999");
+
+        let qualifiers = qualifiers_from_str("KZDV/QNVAS/IV/BO/AE/000/999/3951S10440W025").unwrap();
+        let LatLong(lat, lon) = qualifiers.center.clone().unwrap();
+        assert!(lat < 0.0 && lon < 0.0);
+        assert!(qualifiers_from_str("EDGG/QMRLC").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_highlight_from_code() {
+        assert_eq!(highlight_from_code("QMRLC"), Some(Highlight::RunwayClosure));
+        assert_eq!(
+            highlight_from_code("QFALC"),
+            Some(Highlight::AerodromeClosure)
+        );
+        assert_eq!(highlight_from_code("QNVAS"), Some(Highlight::NavaidOutage));
+        assert_eq!(highlight_from_code("QICAS"), Some(Highlight::NavaidOutage));
+        assert_eq!(highlight_from_code("QMXLC"), None);
+        assert_eq!(highlight_from_code("QMRXX"), None);
+    }
+
+    #[tokio::test]
+    async fn test_highlight_from_text() {
+        assert_eq!(
+            highlight_from_text("RWY 06/24 CLSD"),
+            Some(Highlight::RunwayClosure)
+        );
+        assert_eq!(
+            highlight_from_text("AD CLSD TO VFR FLIGHTS"),
+            Some(Highlight::AerodromeClosure)
+        );
+        assert_eq!(
+            highlight_from_text("ILS RWY 24 U/S"),
+            Some(Highlight::NavaidOutage)
+        );
+        assert_eq!(
+            highlight_from_text("TWY A BTN RWY 14L AND APRON CLSD"),
+            None
+        );
+        assert_eq!(highlight_from_text("CRANE ERECTED 500FT AGL"), None);
+    }
+
+    #[tokio::test]
+    async fn test_notam_text_only() {
+        let notam = Notam::from_raw("!DEN 06/123 DEN NAV VOR U/S 2406210600-2406211800").unwrap();
+        assert_eq!(notam.id, None);
+        assert_eq!(notam.qualifiers, None);
+        assert_eq!(notam.highlight, Some(Highlight::NavaidOutage));
+        assert_eq!(notam.assess(), Assessment::Marginal);
+        assert!(Notam::from_raw("  ").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notams_from_json() {
+        let json: Value = serde_json::json!({"reports": [
+            {"raw": "A0001/24 NOTAMN A) EDDK B) 2406200600 C) 2406301800 E) BIRD ACTIVITY"},
+            {"raw": "A0002/24 NOTAMN A) EDDK B) 2406200600 C) 2406210500 E) RWY 06/24 CLSD"},
+            {"raw": "A0003/24 NOTAMC A0004/24 A) EDDK B) 2406210600 E) REF A0004/24 CNL"},
+            {"raw": "A0005/24 NOTAMN A) EDDK B) 2406220600 C) PERM E) AD CLSD"}
+        ]});
+        let time = Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap();
+        let notams = notams_from_json(&json, time).unwrap();
+        let ids: Vec<Option<String>> = notams.into_iter().map(|notam| notam.id).collect();
+        assert_eq!(
+            ids,
+            vec![Some("A0005/24".to_string()), Some("A0001/24".to_string())]
+        );
+
+        let empty: Value = serde_json::json!([]);
+        assert!(notams_from_json(&empty, time).unwrap().is_empty());
+        let error: Value = serde_json::json!({"error": "Station not found"});
+        assert!(notams_from_json(&error, time).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_notam_from_json() {
+        let json: Value = serde_json::from_str(
+            r#"{"raw": "A0456/24 NOTAMR A0123/24 Q) EDGG/QFALC/IV/NBO/A/000/999/5052N00709E005 A) EDDK B) 2406220000 C) PERM E) AD CLSD"}"#,
+        )
+        .unwrap();
+        let notam = Notam::from_json(&json).unwrap();
+        assert_eq!(notam.id, Some("A0456/24".to_string()));
+        assert_eq!(notam.kind, NotamKind::Replace);
+        assert!(notam.permanent);
+        assert_eq!(notam.valid_to, None);
+        assert_eq!(notam.highlight, Some(Highlight::AerodromeClosure));
+    }
+
+    #[tokio::test]
+    async fn test_notam_colourise() {
+        let notam = Notam::from_raw(RAW).unwrap();
+        assert_eq!(
+            notam.colourise().clear().to_string(),
+            "A1234/24 EDDK 210600Z-211800Z EST RWY CLSD RWY 14L/32R CLSD DUE TO WIP"
+        );
+        let notam =
+            Notam::from_raw("A0001/24 NOTAMN A) EDDK B) 2406210600 C) PERM E) BIRD ACTIVITY")
+                .unwrap();
+        assert_eq!(
+            notam.colourise().clear().to_string(),
+            "A0001/24 EDDK 210600Z-PERM BIRD ACTIVITY"
+        );
+    }
+}
@@ -10,20 +10,32 @@
 // limitations under the License.
 // WxFetch - position.rs
 
+//! Positions to fetch weather for, given as airfield, coordinates or derived from the IP address.
+
+use serde::Serialize;
 use serde_json::Value;
 use std::fmt::Display;
 
 #[derive(Debug, Clone, PartialEq)]
+/// Position to fetch weather for.
 pub enum Position {
+    /// ICAO code of an airfield, e.g. "EDDK".
     Airfield(String),
+    /// Position derived from the public IP address.
     GeoIP,
+    /// Latitude and longitude in decimal degrees.
     LatLong(LatLong),
 }
 
 impl Position {
+    /// Returns the position in the form expected by `AvWx`, either an ICAO code or "lat,long". Looks up the location of the IP address for [`Position::GeoIP`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is [`Position::GeoIP`] and the location cannot be determined, e.g. without internet connection.
     pub async fn get_location_str(&self) -> String {
         match self {
-            Self::Airfield(icao_code) => icao_code.to_string(),
+            Self::Airfield(icao_code) => icao_code.clone(),
             Self::LatLong(latlong) => latlong.to_string(),
             Self::GeoIP => get_geoip()
                 .await
@@ -33,7 +45,8 @@ impl Position {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+/// Latitude and longitude in decimal degrees, north and east positive.
 pub struct LatLong(pub f64, pub f64);
 
 impl Display for LatLong {
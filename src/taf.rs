@@ -0,0 +1,670 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - taf.rs
+
+//! Decoding of TAFs into change periods and their assessment against the personal minima of a [`Config`].
+
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Datelike, Months, TimeDelta, Timelike, Utc};
+use colored::{ColoredString, Colorize};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+pub mod timeline;
+pub mod verification;
+
+use crate::Config;
+use crate::metar::visibility::join_visibility_groups;
+use crate::metar::windshear::join_wind_shear_groups;
+use crate::metar::{
+    AssessedField, Assessment, ReportModifier, WxField, build_regex, field_from_str,
+    is_station_code,
+};
+
+/// Validity period like "2112/2218".
+static PERIOD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    build_regex(r"^(?<from_day>\d{2})(?<from_hour>\d{2})/(?<to_day>\d{2})(?<to_hour>\d{2})$")
+});
+/// Issue time like "211100Z".
+static ISSUE_TIME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^(?<day>\d{2})(?<hour>\d{2})(?<minute>\d{2})Z$"));
+/// Start of an FM group like "FM220600".
+static FM_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| build_regex(r"^FM(?<day>\d{2})(?<hour>\d{2})(?<minute>\d{2})$"));
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+/// A terminal aerodrome forecast (TAF), consisting of the base forecast and its change groups.
+pub struct Taf {
+    /// ICAO code of the issuing station.
+    pub station: String,
+    /// Issue time, if given.
+    pub issued: Option<DateTime<Utc>>,
+    /// Start of the validity period.
+    pub valid_from: DateTime<Utc>,
+    /// End of the validity period.
+    pub valid_to: DateTime<Utc>,
+    /// Report modifiers (AMD, COR, NIL, CNL).
+    pub modifiers: Vec<ReportModifier>,
+    /// Forecast periods in the order of the report. The first period is always the base forecast.
+    pub periods: Vec<TafPeriod>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+/// The base forecast or one of the change groups of a TAF.
+pub struct TafPeriod {
+    /// Kind of change.
+    pub change: TafChange,
+    /// Start of the period.
+    pub from: DateTime<Utc>,
+    /// End of the period. The base forecast and FM groups last until the next FM group or the end of the validity, for BECMG groups this is the end of the transition.
+    pub to: DateTime<Utc>,
+    /// Forecast conditions.
+    pub fields: Vec<WxField>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Kind of change announced by a TAF period.
+pub enum TafChange {
+    /// Base forecast.
+    Base,
+    /// Conditions change completely at the start of the period (FM).
+    From,
+    /// Conditions change gradually during the period (BECMG).
+    Becmg,
+    /// Temporary fluctuations during the period (TEMPO).
+    Tempo,
+    /// Conditions occur with the given probability in percent (PROB30, PROB40).
+    Prob(u8),
+    /// Temporary fluctuations occur with the given probability in percent (PROB30 TEMPO).
+    ProbTempo(u8),
+}
+
+#[derive(Debug, Serialize)]
+/// Decoded TAF with all fields assessed against the personal minima, for JSON output.
+pub struct TafReport {
+    /// ICAO code of the station.
+    pub station: String,
+    /// Issue time, if given.
+    pub issued: Option<DateTime<Utc>>,
+    /// Start of the validity period.
+    pub valid_from: DateTime<Utc>,
+    /// End of the validity period.
+    pub valid_to: DateTime<Utc>,
+    /// Modifiers such as AMD or COR.
+    pub modifiers: Vec<ReportModifier>,
+    /// Worst assessment of all periods.
+    pub assessment: Assessment,
+    /// The base forecast followed by all change groups.
+    pub periods: Vec<TafPeriodReport>,
+}
+
+#[derive(Debug, Serialize)]
+/// A TAF period with all fields assessed against the personal minima, for JSON output.
+pub struct TafPeriodReport {
+    /// Kind of change the period introduces.
+    pub change: TafChange,
+    /// Start of the period.
+    pub from: DateTime<Utc>,
+    /// End of the period.
+    pub to: DateTime<Utc>,
+    /// Worst assessment of all fields.
+    pub assessment: Assessment,
+    /// Forecast fields.
+    pub fields: Vec<AssessedField>,
+}
+
+impl Taf {
+    /// Parses a TAF as returned by `AvWx`. The forecast is decoded from the raw report, the report time is used to resolve the days given in the report.
+    #[must_use]
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let raw = json.get("raw")?.as_str()?;
+        let reference = json
+            .get("time")
+            .and_then(|time| time.get("dt"))
+            .and_then(Value::as_str)
+            .and_then(|dt| DateTime::parse_from_rfc3339(dt).ok())
+            .map_or_else(Utc::now, |dt| dt.to_utc());
+        Self::from_raw_at(raw, reference)
+    }
+
+    /// Parses a TAF from its raw textual representation, e.g. "TAF EDDK 211100Z 2112/2218 27010KT 9999 SCT030 BECMG 2118/2120 VRB03KT". Groups that cannot be decoded are skipped. Returns None if no valid station code or validity period is found.
+    #[must_use]
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        Self::from_raw_at(raw, Utc::now())
+    }
+
+    /// Like `from_raw`, but resolves the times of the report relative to `reference` instead of the current time. The issue time is placed in the most recent month in which it is not after `reference`, all other times as close to the issue time as possible.
+    #[must_use]
+    pub fn from_raw_at(raw: &str, reference: DateTime<Utc>) -> Option<Self> {
+        let mut tokens = raw
+            .split_whitespace()
+            .map(|token| token.trim_end_matches('='))
+            .filter(|token| !token.is_empty());
+
+        let mut modifiers: Vec<ReportModifier> = Vec::new();
+        let mut station = tokens.next()?;
+        while matches!(station, "TAF" | "AMD" | "COR") {
+            if let Ok(modifier) = station.parse() {
+                modifiers.push(modifier);
+            }
+            station = tokens.next()?;
+        }
+        if !is_station_code(station) {
+            return None;
+        }
+
+        let groups = join_visibility_groups(join_wind_shear_groups(tokens));
+        let mut groups = groups.iter().map(String::as_str).peekable();
+
+        let issued = groups
+            .peek()
+            .and_then(|group| issue_time_from_str(group, reference));
+        if issued.is_some() {
+            groups.next();
+        }
+        let reference = issued.unwrap_or(reference);
+        let (valid_from, valid_to) = groups
+            .next()
+            .and_then(|group| period_from_str(group, reference))?;
+
+        let mut periods: Vec<TafPeriod> = vec![TafPeriod {
+            change: TafChange::Base,
+            from: valid_from,
+            to: valid_to,
+            fields: Vec::new(),
+        }];
+
+        while let Some(group) = groups.next() {
+            if group == "RMK" {
+                break;
+            }
+            if let Ok(modifier) = group.parse() {
+                modifiers.push(modifier);
+            } else if let Some(from) = fm_from_str(group, reference) {
+                periods.push(TafPeriod {
+                    change: TafChange::From,
+                    from,
+                    to: valid_to,
+                    fields: Vec::new(),
+                });
+            } else if let Some(mut change) = change_from_str(group) {
+                if let TafChange::Prob(probability) = change
+                    && groups.next_if_eq(&"TEMPO").is_some()
+                {
+                    change = TafChange::ProbTempo(probability);
+                }
+                let (from, to) = groups
+                    .next_if(|group| period_from_str(group, reference).is_some())
+                    .and_then(|group| period_from_str(group, reference))
+                    .unwrap_or((valid_from, valid_to));
+                periods.push(TafPeriod {
+                    change,
+                    from,
+                    to,
+                    fields: Vec::new(),
+                });
+            } else if let Some(field) = field_from_str(group)
+                && let Some(period) = periods.last_mut()
+            {
+                period.fields.push(field);
+            }
+        }
+
+        let fm_starts: Vec<DateTime<Utc>> = periods
+            .iter()
+            .filter(|period| period.change == TafChange::From)
+            .map(|period| period.from)
+            .collect();
+        for period in &mut periods {
+            if matches!(period.change, TafChange::Base | TafChange::From) {
+                period.to = fm_starts
+                    .iter()
+                    .copied()
+                    .find(|start| *start > period.from)
+                    .unwrap_or(valid_to);
+            }
+        }
+
+        Some(Taf {
+            station: station.to_string(),
+            issued,
+            valid_from,
+            valid_to,
+            modifiers,
+            periods,
+        })
+    }
+
+    /// Assesses all fields against the personal minima and converts them to the display units, for JSON output.
+    #[must_use]
+    pub fn report(&self, config: &Config) -> TafReport {
+        let periods: Vec<TafPeriodReport> = self
+            .periods
+            .iter()
+            .map(|period| period.report(config))
+            .collect();
+        TafReport {
+            station: self.station.clone(),
+            issued: self.issued,
+            valid_from: self.valid_from,
+            valid_to: self.valid_to,
+            modifiers: self.modifiers.clone(),
+            assessment: periods
+                .iter()
+                .map(|period| period.assessment)
+                .max()
+                .unwrap_or(Assessment::Good),
+            periods,
+        }
+    }
+
+    /// Renders the TAF with one line per period, every field coloured according to the personal minima.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let mut output: ColoredString =
+            format!("{} {}", "TAF".bold(), self.station.bright_white().on_blue()).into();
+        for modifier in &self.modifiers {
+            output = format!("{output} {}", modifier.colourise()).into();
+        }
+        if let Some(issued) = self.issued {
+            output = format!("{output} {}", issued.format("%d%H%MZ").to_string().white()).into();
+        }
+        output = format!(
+            "{output} {}",
+            format_period(self.valid_from, self.valid_to).white()
+        )
+        .into();
+
+        for period in &self.periods {
+            let line = period.colourise(config);
+            output = if period.change == TafChange::Base {
+                format!("{output} {line}").into()
+            } else {
+                format!("{output}\n    {line}").into()
+            };
+        }
+        output
+    }
+}
+
+impl TafPeriod {
+    /// Assesses all fields against the personal minima and converts them to the display units, for JSON output.
+    #[must_use]
+    pub fn report(&self, config: &Config) -> TafPeriodReport {
+        let fields: Vec<AssessedField> = self
+            .fields
+            .iter()
+            .map(|field| AssessedField::new(field, config))
+            .collect();
+        TafPeriodReport {
+            change: self.change,
+            from: self.from,
+            to: self.to,
+            assessment: fields
+                .iter()
+                .map(|field| field.assessment)
+                .max()
+                .unwrap_or(Assessment::Good),
+            fields,
+        }
+    }
+
+    /// Renders the change group with its times followed by the colourised fields. The base forecast only renders its fields.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let mut output: ColoredString = match self.change {
+            TafChange::Base => "".into(),
+            TafChange::From => format!("FM{}", self.from.format("%d%H%M")).bold(),
+            TafChange::Becmg | TafChange::Tempo => format!(
+                "{} {}",
+                self.change.to_string().bold(),
+                format_period(self.from, self.to).white()
+            )
+            .into(),
+            TafChange::Prob(_) | TafChange::ProbTempo(_) => format!(
+                "{} {}",
+                self.change.to_string().bold().yellow(),
+                format_period(self.from, self.to).white()
+            )
+            .into(),
+        };
+        for field in &self.fields {
+            output = if output.is_empty() {
+                field.colourise(config)
+            } else {
+                format!("{output} {}", field.colourise(config)).into()
+            };
+        }
+        output
+    }
+}
+
+impl Display for TafChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TafChange::Base => write!(f, ""),
+            TafChange::From => write!(f, "FM"),
+            TafChange::Becmg => write!(f, "BECMG"),
+            TafChange::Tempo => write!(f, "TEMPO"),
+            TafChange::Prob(probability) => write!(f, "PROB{probability}"),
+            TafChange::ProbTempo(probability) => write!(f, "PROB{probability} TEMPO"),
+        }
+    }
+}
+
+/// Splits raw TAF text into one report per TAF. Change groups are commonly printed on lines of their own, so a line only starts a new report if it begins with "TAF" or with a station code followed by a time group.
+#[must_use]
+pub fn split_raw_tafs(text: &str) -> Vec<String> {
+    let mut tafs: Vec<String> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let mut tokens = line.split_whitespace();
+        let first = tokens.next().unwrap_or_default();
+        let second = tokens.next().unwrap_or_default();
+        let starts_report = first == "TAF"
+            || (is_station_code(first) && (second.ends_with('Z') || PERIOD_REGEX.is_match(second)));
+        match tafs.last_mut() {
+            Some(taf) if !starts_report => {
+                taf.push(' ');
+                taf.push_str(line);
+            }
+            _ => tafs.push(line.to_string()),
+        }
+    }
+    tafs
+}
+
+/// Formats a period as ddhh/ddhh. Midnight at the end of a period is written as hour 24 of the previous day, as in the report.
+fn format_period(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let end = if to.hour() == 0 && to.minute() == 0 {
+        format!("{:02}24", (to - TimeDelta::days(1)).day())
+    } else {
+        to.format("%d%H").to_string()
+    };
+    format!("{}/{end}", from.format("%d%H"))
+}
+
+/// Parses a validity period like "2112/2218".
+fn period_from_str(repr: &str, reference: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let matches = PERIOD_REGEX.captures(repr)?;
+    let from = day_time(
+        matches["from_day"].parse().ok()?,
+        matches["from_hour"].parse().ok()?,
+        0,
+        reference,
+    )?;
+    let to = day_time(
+        matches["to_day"].parse().ok()?,
+        matches["to_hour"].parse().ok()?,
+        0,
+        from,
+    )?;
+    Some((from, to))
+}
+
+/// Parses an issue time like "211100Z". A forecast cannot be issued after `reference`, so the time is placed in the most recent month for which it is not later.
+fn issue_time_from_str(repr: &str, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let matches = ISSUE_TIME_REGEX.captures(repr)?;
+    day_time_candidates(
+        matches["day"].parse().ok()?,
+        matches["hour"].parse().ok()?,
+        matches["minute"].parse().ok()?,
+        reference,
+    )
+    .filter(|datetime| *datetime <= reference)
+    .max()
+}
+
+/// Parses the start of an FM group like "FM220600".
+fn fm_from_str(repr: &str, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let matches = FM_REGEX.captures(repr)?;
+    day_time(
+        matches["day"].parse().ok()?,
+        matches["hour"].parse().ok()?,
+        matches["minute"].parse().ok()?,
+        reference,
+    )
+}
+
+/// Parses the keywords starting BECMG, TEMPO and PROB groups.
+fn change_from_str(repr: &str) -> Option<TafChange> {
+    match repr {
+        "BECMG" => Some(TafChange::Becmg),
+        "TEMPO" => Some(TafChange::Tempo),
+        _ => repr
+            .strip_prefix("PROB")
+            .and_then(|probability| probability.parse().ok())
+            .map(TafChange::Prob),
+    }
+}
+
+/// Resolves a day of the month and a time to the date closest to `reference`, as TAF groups do not contain month or year.
+fn day_time(day: u32, hour: u32, minute: u32, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    day_time_candidates(day, hour, minute, reference)
+        .min_by_key(|datetime| (*datetime - reference).num_seconds().abs())
+}
+
+/// Dates with the given day of the month and time in the month of `reference` and the months before and after. Hour 24 denotes the end of the day.
+fn day_time_candidates(
+    day: u32,
+    hour: u32,
+    minute: u32,
+    reference: DateTime<Utc>,
+) -> impl Iterator<Item = DateTime<Utc>> {
+    let (hour, extra_days) = if hour == 24 { (0, 1) } else { (hour, 0) };
+    let first_of_month = reference.date_naive().with_day(1);
+    [
+        first_of_month.and_then(|first| first.checked_sub_months(Months::new(1))),
+        first_of_month,
+        first_of_month.and_then(|first| first.checked_add_months(Months::new(1))),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(move |month| month.with_day(day))
+    .filter_map(move |date| date.and_hms_opt(hour, minute, 0))
+    .map(move |datetime| datetime.and_utc() + TimeDelta::days(extra_days))
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::Taf;
+
+    /// A TAF with every kind of change group, issued on 21 June 2024.
+    pub(crate) const RAW: &str = "TAF EDDK 211100Z 2112/2218 27010KT 9999 SCT030 BECMG 2118/2120 VRB03KT TEMPO 2200/2206 0800 FG PROB30 TEMPO 2206/2209 4000 TSRA BKN015CB FM221200 30015G25KT 9999 BKN020=";
+
+    /// The issue time of [`RAW`].
+    pub(crate) fn reference() -> DateTime<Utc> {
+        time(21, 11)
+    }
+
+    /// A full hour in June 2024.
+    pub(crate) fn time(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap()
+    }
+
+    /// [`RAW`] decoded at its issue time.
+    pub(crate) fn taf() -> Taf {
+        Taf::from_raw_at(RAW, reference()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::fixtures::{RAW, reference, time};
+    use super::*;
+    use crate::metar::WindDirection;
+    use crate::metar::units::{Speed, SpeedUnit};
+
+    #[tokio::test]
+    async fn test_from_raw_header() {
+        let taf = Taf::from_raw_at(RAW, reference()).unwrap();
+        assert_eq!(taf.station, "EDDK");
+        assert_eq!(taf.issued, Some(time(21, 11)));
+        assert_eq!(taf.valid_from, time(21, 12));
+        assert_eq!(taf.valid_to, time(22, 18));
+        assert!(taf.modifiers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_from_raw_periods() {
+        let taf = Taf::from_raw_at(RAW, reference()).unwrap();
+        let changes: Vec<TafChange> = taf.periods.iter().map(|period| period.change).collect();
+        let expected = vec![
+            TafChange::Base,
+            TafChange::Becmg,
+            TafChange::Tempo,
+            TafChange::ProbTempo(30),
+            TafChange::From,
+        ];
+        assert_eq!(changes, expected);
+
+        let base = &taf.periods[0];
+        assert_eq!((base.from, base.to), (time(21, 12), time(22, 12)));
+        assert_eq!(base.fields.len(), 3);
+        assert_eq!(
+            base.fields[0],
+            WxField::Wind {
+                direction: WindDirection::Heading(270),
+                strength: Speed::new(10, SpeedUnit::Kt),
+                gusts: None,
+            }
+        );
+
+        let becmg = &taf.periods[1];
+        assert_eq!((becmg.from, becmg.to), (time(21, 18), time(21, 20)));
+        assert_eq!(taf.periods[2].fields.len(), 2);
+        assert_eq!(taf.periods[3].fields.len(), 3);
+
+        let fm = &taf.periods[4];
+        assert_eq!((fm.from, fm.to), (time(22, 12), time(22, 18)));
+        assert_eq!(fm.fields.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_from_raw_modifiers() {
+        let taf = Taf::from_raw_at("TAF AMD EDDK 211300Z 2112/2218 CNL", reference()).unwrap();
+        assert_eq!(
+            taf.modifiers,
+            vec![ReportModifier::Amd, ReportModifier::Cnl]
+        );
+        assert!(taf.periods[0].fields.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_from_raw_invalid() {
+        assert!(Taf::from_raw_at("TAF 2112/2218 27010KT", reference()).is_none());
+        assert!(Taf::from_raw_at("TAF EDDK 211100Z 27010KT", reference()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_from_raw_without_issue_time() {
+        let taf = Taf::from_raw_at("EDDK 2112/2218 27010KT CAVOK", reference()).unwrap();
+        assert_eq!(taf.issued, None);
+        assert_eq!(taf.periods[0].fields[1], WxField::Cavok);
+    }
+
+    #[tokio::test]
+    async fn test_from_raw_stops_at_remarks() {
+        let taf = Taf::from_raw_at(
+            "KJFK 211120Z 2112/2218 27010KT P6SM FEW250 RMK NXT FCST BY 18Z",
+            reference(),
+        )
+        .unwrap();
+        assert_eq!(taf.periods[0].fields.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_period_from_str() {
+        let actual = period_from_str("2118/2124", reference());
+        assert_eq!(actual, Some((time(21, 18), time(22, 0))));
+        assert_eq!(period_from_str("211800Z", reference()), None);
+    }
+
+    #[tokio::test]
+    async fn test_issue_time_from_str() {
+        let expected = Utc.with_ymd_and_hms(2024, 5, 25, 11, 0, 0).unwrap();
+        assert_eq!(issue_time_from_str("251100Z", reference()), Some(expected));
+        assert_eq!(
+            issue_time_from_str("211100Z", reference()),
+            Some(time(21, 11))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_period() {
+        assert_eq!(format_period(time(22, 0), time(22, 6)), "2200/2206");
+        assert_eq!(format_period(time(21, 18), time(22, 0)), "2118/2124");
+    }
+
+    #[tokio::test]
+    async fn test_day_time_month_boundary() {
+        let reference = Utc.with_ymd_and_hms(2024, 6, 30, 18, 0, 0).unwrap();
+        let expected = Utc.with_ymd_and_hms(2024, 7, 1, 6, 0, 0).unwrap();
+        assert_eq!(day_time(1, 6, 0, reference), Some(expected));
+        let expected = Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+        assert_eq!(day_time(30, 24, 0, reference), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_change_from_str() {
+        assert_eq!(change_from_str("BECMG"), Some(TafChange::Becmg));
+        assert_eq!(change_from_str("PROB40"), Some(TafChange::Prob(40)));
+        assert_eq!(change_from_str("PROBABLY"), None);
+    }
+
+    #[tokio::test]
+    async fn test_split_raw_tafs() {
+        let text = "TAF EDDK 211100Z 2112/2218 27010KT 9999 SCT030\n      BECMG 2118/2120 VRB03KT\nEDDL 211100Z 2112/2218 25008KT CAVOK\n  TEMPO 2112/2116 4000 RA";
+        let expected = vec![
+            "TAF EDDK 211100Z 2112/2218 27010KT 9999 SCT030 BECMG 2118/2120 VRB03KT".to_string(),
+            "EDDL 211100Z 2112/2218 25008KT CAVOK TEMPO 2112/2116 4000 RA".to_string(),
+        ];
+        assert_eq!(split_raw_tafs(text), expected);
+    }
+
+    #[tokio::test]
+    async fn test_from_json() {
+        let json: Value = serde_json::from_str(
+            "{\"raw\":\"EDDK 211100Z 2112/2218 27010KT 9999 SCT030\",\"time\":{\"dt\":\"2024-06-21T11:00:00Z\"}}",
+        )
+        .unwrap();
+        let taf = Taf::from_json(&json).unwrap();
+        assert_eq!(taf.valid_from, time(21, 12));
+    }
+
+    #[tokio::test]
+    async fn test_colourise() {
+        let taf = Taf::from_raw_at(RAW, reference()).unwrap();
+        let output = taf.colourise(&Config::default()).clear().to_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("TAF EDDK 211100Z 2112/2218 27010KT"));
+        assert_eq!(lines[1].trim(), "BECMG 2118/2120 VRB03KT");
+        assert!(lines[3].trim().starts_with("PROB30 TEMPO 2206/2209"));
+        assert!(lines[4].trim().starts_with("FM221200 30015G25KT"));
+    }
+
+    #[tokio::test]
+    async fn test_report() {
+        let taf = Taf::from_raw_at(RAW, reference()).unwrap();
+        let report = taf.report(&Config::default());
+        assert_eq!(report.periods[0].assessment, Assessment::Good);
+        assert_eq!(report.periods[2].assessment, Assessment::Bad);
+        assert_eq!(report.assessment, Assessment::Bad);
+    }
+}
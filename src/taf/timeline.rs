@@ -0,0 +1,495 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - taf/timeline.rs
+
+//! Hourly timeline of the flight category forecast by a TAF.
+
+use std::fmt::Display;
+use std::mem::{Discriminant, discriminant};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use colored::{Color, ColoredString, Colorize};
+use serde::Serialize;
+
+use super::{Taf, TafChange, TafPeriod};
+use crate::Config;
+use crate::metar::clouds::{CloudType, Clouds};
+use crate::metar::rvr::RvrLimit;
+use crate::metar::units::{Altitude, AltitudeUnit, Distance, DistanceUnit};
+use crate::metar::{AssessedField, Assessment, WxField};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Flight category as defined by the FAA, determined by the ceiling and the visibility. Ordered from best to worst.
+pub enum FlightCategory {
+    /// Visual flight rules: ceiling above 3000ft and visibility above 5SM.
+    Vfr,
+    /// Marginal VFR: ceiling from 1000ft to 3000ft or visibility from 3SM to 5SM.
+    Mvfr,
+    /// Instrument flight rules: ceiling from 500ft below 1000ft or visibility from 1SM below 3SM.
+    Ifr,
+    /// Low IFR: ceiling below 500ft or visibility below 1SM.
+    Lifr,
+}
+
+#[derive(Debug, Serialize)]
+/// Hour-by-hour view of a TAF, for planning a departure window.
+pub struct Timeline {
+    /// ICAO code of the issuing station.
+    pub station: String,
+    /// Effective conditions for every hour of the validity period.
+    pub hours: Vec<HourlyForecast>,
+}
+
+#[derive(Debug, Serialize)]
+/// Effective conditions forecast for one hour.
+pub struct HourlyForecast {
+    /// Start of the hour.
+    pub time: DateTime<Utc>,
+    /// Flight category of the prevailing conditions.
+    pub category: FlightCategory,
+    /// Worst flight category including temporary conditions.
+    pub worst_category: FlightCategory,
+    /// Worst assessment against the personal minima, including temporary conditions.
+    pub assessment: Assessment,
+    /// Prevailing conditions: the base forecast or the FM group in effect, updated by all BECMG groups completed by then.
+    pub fields: Vec<AssessedField>,
+    /// TEMPO and PROB groups in effect, as well as BECMG groups still in transition.
+    pub temporary: Vec<TemporaryConditions>,
+}
+
+#[derive(Debug, Serialize)]
+/// Conditions expected temporarily or with a certain probability during an hour.
+pub struct TemporaryConditions {
+    /// The TEMPO, PROB or BECMG group.
+    pub change: TafChange,
+    /// Fields it forecasts.
+    pub fields: Vec<AssessedField>,
+}
+
+#[derive(PartialEq, Eq)]
+/// Groups of fields that replace each other when a change group is applied.
+enum FieldKind {
+    Wind,
+    Visibility,
+    Clouds,
+    Weather,
+    Other(Discriminant<WxField>),
+}
+
+impl Timeline {
+    /// Resolves the overlapping periods of the TAF into hourly conditions, assessed against the personal minima.
+    #[must_use]
+    pub fn new(taf: &Taf, config: &Config) -> Self {
+        let mut hours: Vec<HourlyForecast> = Vec::new();
+        let mut time = taf.valid_from;
+        while time < taf.valid_to {
+            hours.push(HourlyForecast::new(taf, time, config));
+            time += TimeDelta::hours(1);
+        }
+        Timeline {
+            station: taf.station.clone(),
+            hours,
+        }
+    }
+
+    /// Renders one line per hour with a bar for the flight category and one for the personal minima, followed by the effective conditions.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let mut output: ColoredString = self.station.bright_white().on_blue();
+        for hour in &self.hours {
+            output = format!("{output}\n{}", hour.colourise(config)).into();
+        }
+        output
+    }
+}
+
+impl HourlyForecast {
+    /// The conditions forecast by the TAF for the hour starting at `time`.
+    #[must_use]
+    pub fn new(taf: &Taf, time: DateTime<Utc>, config: &Config) -> Self {
+        let prevailing = prevailing_fields(taf, time);
+        let category = FlightCategory::from_fields(&prevailing);
+
+        let temporary: Vec<(TafChange, &Vec<WxField>)> = temporary_periods(taf, time)
+            .map(|period| (period.change, &period.fields))
+            .collect();
+
+        let worst_category = forecast_alternatives(taf, time)
+            .iter()
+            .map(|fields| FlightCategory::from_fields(fields))
+            .fold(category, FlightCategory::max);
+
+        let assess = |fields: &[WxField]| -> Vec<AssessedField> {
+            fields
+                .iter()
+                .map(|field| AssessedField::new(field, config))
+                .collect()
+        };
+        let fields = assess(&prevailing);
+        let temporary: Vec<TemporaryConditions> = temporary
+            .into_iter()
+            .map(|(change, fields)| TemporaryConditions {
+                change,
+                fields: assess(fields),
+            })
+            .collect();
+        let assessment = fields
+            .iter()
+            .chain(temporary.iter().flat_map(|conditions| &conditions.fields))
+            .map(|field| field.assessment)
+            .max()
+            .unwrap_or(Assessment::Good);
+
+        HourlyForecast {
+            time,
+            category,
+            worst_category,
+            assessment,
+            fields,
+            temporary,
+        }
+    }
+
+    /// One line for the hour, with a bar in the colour of the flight category.
+    #[must_use]
+    pub fn colourise(&self, config: &Config) -> ColoredString {
+        let bar = "█";
+        let mut output: ColoredString = format!(
+            "{} {}{}{} {:<4}",
+            self.time.format("%d %HZ").to_string().white(),
+            bar.color(self.category),
+            bar.color(self.worst_category),
+            bar.color(Color::from(self.assessment)),
+            self.category.colourise(),
+        )
+        .into();
+        for field in &self.fields {
+            output = format!("{output} {}", field.field.colourise(config)).into();
+        }
+        for conditions in &self.temporary {
+            output = format!("{output} {}", conditions.change.to_string().bold()).into();
+            for field in &conditions.fields {
+                output = format!("{output} {}", field.field.colourise(config)).into();
+            }
+        }
+        output
+    }
+}
+
+impl FlightCategory {
+    /// Determines the category from the lowest broken or overcast layer (or the vertical visibility) and the prevailing visibility. Missing values do not restrict the category.
+    #[must_use]
+    pub fn from_fields(fields: &[WxField]) -> Self {
+        let feet = |value| Altitude::new(value, AltitudeUnit::Ft);
+        let by_ceiling = match ceiling(fields) {
+            Some(ceiling) if ceiling < feet(500) => FlightCategory::Lifr,
+            Some(ceiling) if ceiling < feet(1000) => FlightCategory::Ifr,
+            Some(ceiling) if ceiling <= feet(3000) => FlightCategory::Mvfr,
+            _ => FlightCategory::Vfr,
+        };
+        let miles = |value| Distance::new(value, DistanceUnit::Mi);
+        let by_visibility = match visibility(fields) {
+            Some(visibility) if visibility < miles(1) => FlightCategory::Lifr,
+            Some(visibility) if visibility < miles(3) => FlightCategory::Ifr,
+            Some(visibility) if visibility <= miles(5) => FlightCategory::Mvfr,
+            _ => FlightCategory::Vfr,
+        };
+        by_ceiling.max(by_visibility)
+    }
+
+    /// The category in its conventional colour.
+    #[must_use]
+    pub fn colourise(self) -> ColoredString {
+        self.to_string().color(self)
+    }
+}
+
+impl From<FlightCategory> for Color {
+    fn from(category: FlightCategory) -> Self {
+        match category {
+            FlightCategory::Vfr => Color::Green,
+            FlightCategory::Mvfr => Color::Blue,
+            FlightCategory::Ifr => Color::Red,
+            FlightCategory::Lifr => Color::Magenta,
+        }
+    }
+}
+
+impl Display for FlightCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str_repr = match self {
+            FlightCategory::Vfr => "VFR",
+            FlightCategory::Mvfr => "MVFR",
+            FlightCategory::Ifr => "IFR",
+            FlightCategory::Lifr => "LIFR",
+        };
+        write!(f, "{str_repr}")
+    }
+}
+
+/// Lowest broken or overcast cloud layer or vertical visibility, None if there is no ceiling.
+pub(crate) fn ceiling(fields: &[WxField]) -> Option<Altitude> {
+    fields
+        .iter()
+        .filter_map(|field| match field {
+            WxField::Clouds(Clouds::Bkn | Clouds::Ovc | Clouds::Vv, Some(altitude), _) => {
+                Some(*altitude)
+            }
+            WxField::Clouds(Clouds::Vv, None, _) => Some(Altitude::new(0, AltitudeUnit::Ft)),
+            _ => None,
+        })
+        .min()
+}
+
+/// Prevailing visibility, with CAVOK counting as 10km.
+pub(crate) fn visibility(fields: &[WxField]) -> Option<Distance> {
+    fields.iter().find_map(|field| match field {
+        WxField::Visibility { distance, .. } => Some(*distance),
+        WxField::Cavok => Some(cavok_visibility()),
+        _ => None,
+    })
+}
+
+/// All conditions the TAF allows at `time`: the prevailing conditions, followed by the prevailing conditions modified by each temporary period.
+pub(crate) fn forecast_alternatives(taf: &Taf, time: DateTime<Utc>) -> Vec<Vec<WxField>> {
+    let prevailing = prevailing_fields(taf, time);
+    let mut alternatives: Vec<Vec<WxField>> = vec![prevailing.clone()];
+    for period in temporary_periods(taf, time) {
+        let mut conditions = prevailing.clone();
+        apply_changes(&mut conditions, &period.fields);
+        alternatives.push(conditions);
+    }
+    alternatives
+}
+
+/// Periods modifying the prevailing conditions at `time` for a limited time or with a probability: TEMPO and PROB groups as well as BECMG groups still in transition.
+fn temporary_periods(taf: &Taf, time: DateTime<Utc>) -> impl Iterator<Item = &TafPeriod> {
+    taf.periods
+        .iter()
+        .filter(move |period| period.from <= time && time < period.to)
+        .filter(|period| {
+            matches!(
+                period.change,
+                TafChange::Becmg | TafChange::Tempo | TafChange::Prob(_) | TafChange::ProbTempo(_)
+            )
+        })
+}
+
+/// Prevailing conditions at `time`: the base forecast or the latest FM group started by then, updated by every BECMG group completed by then.
+fn prevailing_fields(taf: &Taf, time: DateTime<Utc>) -> Vec<WxField> {
+    let mut fields: Vec<WxField> = Vec::new();
+    for period in &taf.periods {
+        match period.change {
+            TafChange::Base | TafChange::From if period.from <= time => {
+                fields.clone_from(&period.fields);
+            }
+            TafChange::Becmg if period.to <= time => apply_changes(&mut fields, &period.fields),
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// Visibility implied by CAVOK, 10km or more.
+fn cavok_visibility() -> Distance {
+    Distance::new(9999, DistanceUnit::M)
+}
+
+/// Applies the fields of a change group to the conditions, keeping the order of the report. Cloud layers and weather are replaced as a whole, CAVOK replaces visibility, clouds and weather. A change of only some of these expands a prevailing CAVOK into its visibility and NSC first, so that the others are kept.
+fn apply_changes(fields: &mut Vec<WxField>, changes: &[WxField]) {
+    if !changes.contains(&WxField::Cavok)
+        && changes.iter().any(|change| affects_cavok(&kind(change)))
+    {
+        expand_cavok(fields);
+    }
+    let mut pending: Vec<WxField> = changes.to_vec();
+    let mut result: Vec<WxField> = Vec::new();
+    for field in fields.drain(..) {
+        if changes.iter().any(|change| replaces(change, &field)) {
+            let (replacing, rest): (Vec<WxField>, Vec<WxField>) = pending
+                .into_iter()
+                .partition(|change| replaces(change, &field));
+            result.extend(replacing);
+            pending = rest;
+        } else {
+            result.push(field);
+        }
+    }
+    result.extend(pending);
+    *fields = result;
+}
+
+/// Replaces CAVOK by the conditions it stands for: a visibility of 10km or more and no significant cloud.
+fn expand_cavok(fields: &mut Vec<WxField>) {
+    if let Some(index) = fields.iter().position(|field| *field == WxField::Cavok) {
+        let visibility = WxField::Visibility {
+            distance: cavok_visibility(),
+            limit: RvrLimit::Exact,
+            minimum: None,
+        };
+        let clouds = WxField::Clouds(Clouds::Nsc, None, CloudType::None);
+        fields.splice(index..=index, [visibility, clouds]);
+    }
+}
+
+fn affects_cavok(kind: &FieldKind) -> bool {
+    matches!(
+        kind,
+        FieldKind::Visibility | FieldKind::Clouds | FieldKind::Weather
+    )
+}
+
+fn replaces(change: &WxField, field: &WxField) -> bool {
+    let (change_kind, field_kind) = (kind(change), kind(field));
+    change_kind == field_kind || (*change == WxField::Cavok && affects_cavok(&field_kind))
+}
+
+fn kind(field: &WxField) -> FieldKind {
+    match field {
+        WxField::Wind { .. } | WxField::WindVariability { .. } => FieldKind::Wind,
+        WxField::Visibility { .. } | WxField::Cavok => FieldKind::Visibility,
+        WxField::Clouds(..) => FieldKind::Clouds,
+        WxField::WxCode(..) | WxField::Nsw => FieldKind::Weather,
+        field => FieldKind::Other(discriminant(field)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metar::WindDirection;
+    use crate::metar::clouds::clouds_from_str;
+    use crate::metar::visibility::visibility_from_str;
+    use crate::taf::fixtures::{taf, time};
+
+    fn timeline() -> Timeline {
+        Timeline::new(&taf(), &Config::default())
+    }
+
+    #[tokio::test]
+    async fn test_timeline_covers_validity() {
+        let timeline = timeline();
+        assert_eq!(timeline.hours.len(), 30);
+        assert_eq!(timeline.hours[0].time, time(21, 12));
+        assert_eq!(timeline.hours[29].time, time(22, 17));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_becmg() {
+        let timeline = timeline();
+        let in_transition = &timeline.hours[6];
+        assert_eq!(in_transition.time, time(21, 18));
+        assert_eq!(in_transition.temporary[0].change, TafChange::Becmg);
+        assert!(matches!(
+            in_transition.fields[0].field,
+            WxField::Wind { gusts: None, .. }
+        ));
+
+        let completed = &timeline.hours[8];
+        assert!(completed.temporary.is_empty());
+        assert_eq!(completed.fields.len(), 3);
+        assert!(matches!(
+            completed.fields[0].field,
+            WxField::Wind {
+                direction: WindDirection::Variable,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_tempo() {
+        let timeline = timeline();
+        let fog = &timeline.hours[12];
+        assert_eq!(fog.time, time(22, 0));
+        assert_eq!(fog.category, FlightCategory::Vfr);
+        assert_eq!(fog.worst_category, FlightCategory::Lifr);
+        assert_eq!(fog.assessment, Assessment::Bad);
+
+        let thunderstorm = &timeline.hours[18];
+        assert_eq!(thunderstorm.temporary[0].change, TafChange::ProbTempo(30));
+        assert_eq!(thunderstorm.worst_category, FlightCategory::Ifr);
+    }
+
+    #[tokio::test]
+    async fn test_timeline_fm() {
+        let timeline = timeline();
+        let last = timeline.hours.last().unwrap();
+        assert_eq!(last.category, FlightCategory::Mvfr);
+        assert_eq!(last.fields.len(), 3);
+        assert!(last.temporary.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_flight_category_from_fields() {
+        let fields = vec![
+            visibility_from_str("9999").unwrap(),
+            clouds_from_str("OVC008").unwrap(),
+        ];
+        assert_eq!(FlightCategory::from_fields(&fields), FlightCategory::Ifr);
+        let fields = vec![
+            visibility_from_str("4SM").unwrap(),
+            clouds_from_str("FEW005").unwrap(),
+        ];
+        assert_eq!(FlightCategory::from_fields(&fields), FlightCategory::Mvfr);
+        let fields = vec![clouds_from_str("VV///").unwrap()];
+        assert_eq!(FlightCategory::from_fields(&fields), FlightCategory::Lifr);
+        assert_eq!(
+            FlightCategory::from_fields(&[WxField::Cavok]),
+            FlightCategory::Vfr
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flight_category_serialize() {
+        assert_eq!(
+            serde_json::to_value(FlightCategory::Mvfr).unwrap(),
+            serde_json::json!("mvfr")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_changes_cavok() {
+        let mut fields = vec![
+            visibility_from_str("3000").unwrap(),
+            clouds_from_str("BKN008").unwrap(),
+            clouds_from_str("OVC015").unwrap(),
+        ];
+        apply_changes(&mut fields, &[WxField::Cavok]);
+        assert_eq!(fields, vec![WxField::Cavok]);
+        apply_changes(&mut fields, &[clouds_from_str("BKN008").unwrap()]);
+        let expected = vec![
+            visibility_from_str("9999").unwrap(),
+            clouds_from_str("BKN008").unwrap(),
+        ];
+        assert_eq!(fields, expected);
+        assert_eq!(FlightCategory::from_fields(&fields), FlightCategory::Ifr);
+
+        let mut fields = vec![WxField::Cavok];
+        apply_changes(&mut fields, &[visibility_from_str("4000").unwrap()]);
+        let expected = vec![
+            visibility_from_str("4000").unwrap(),
+            clouds_from_str("NSC").unwrap(),
+        ];
+        assert_eq!(fields, expected);
+    }
+
+    #[tokio::test]
+    async fn test_hourly_colourise() {
+        let timeline = timeline();
+        let output = timeline.hours[12]
+            .colourise(&Config::default())
+            .clear()
+            .to_string();
+        assert_eq!(output, "22 00Z ███ VFR  VRB03KT 9999 SCT030 TEMPO 0800 FG");
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright 2024 Robin Arnold
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+// http://www.apache.org/licenses/LICENSE-2.0
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// WxFetch - taf/verification.rs
+
+//! Verification of a TAF against the METARs observed during its validity.
+
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
+
+use super::Taf;
+use super::timeline::{ceiling, forecast_alternatives, visibility};
+use crate::metar::units::{Altitude, AltitudeUnit, Distance, DistanceUnit, Speed, SpeedUnit};
+use crate::metar::{Metar, ReportModifier, WxField};
+
+/// Visibility thresholds in metres. Observed and forecast visibilities are compared by the band between these thresholds they fall into, following the TAF amendment criteria of ICAO Annex 3.
+const VISIBILITY_BANDS: [i64; 7] = [150, 350, 600, 800, 1500, 3000, 5000];
+
+/// Ceiling thresholds in feet, see `VISIBILITY_BANDS`.
+const CEILING_BANDS: [i64; 6] = [100, 200, 500, 1000, 1500, 3000];
+
+/// Difference in knots between the observed and the forecast mean wind or gusts that counts as a bust.
+const WIND_TOLERANCE: i64 = 10;
+
+#[derive(Debug, Serialize)]
+/// Comparison of a TAF with the METARs observed during its validity.
+pub struct Verification {
+    /// ICAO code of the station.
+    pub station: String,
+    /// One check per METAR observed during the validity of the TAF, in the order given.
+    pub checks: Vec<ObservationCheck>,
+}
+
+#[derive(Debug, Serialize)]
+/// Comparison of a single observation with the conditions forecast for its time.
+pub struct ObservationCheck {
+    /// Observation time.
+    pub time: DateTime<Utc>,
+    /// Elements observed outside of what the TAF allowed, empty if the forecast held.
+    pub busts: Vec<Bust>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "element", rename_all = "snake_case")]
+/// An element observed outside the range allowed by the prevailing and temporary conditions of the TAF.
+pub enum Bust {
+    /// Visibility in a band outside the forecast ones.
+    Visibility {
+        /// Observed visibility.
+        observed: Distance,
+        /// Lowest forecast visibility.
+        lowest: Distance,
+        /// Highest forecast visibility.
+        highest: Distance,
+    },
+    /// Ceiling in a band outside the forecast ones. None means no ceiling.
+    Ceiling {
+        /// Observed ceiling.
+        observed: Option<Altitude>,
+        /// Lowest forecast ceiling.
+        lowest: Option<Altitude>,
+        /// Highest forecast ceiling.
+        highest: Option<Altitude>,
+    },
+    /// Mean wind speed differing from all forecast speeds by 10kt or more.
+    Wind {
+        /// Observed mean wind speed.
+        observed: Speed,
+        /// Lowest forecast mean wind speed.
+        lowest: Speed,
+        /// Highest forecast mean wind speed.
+        highest: Speed,
+    },
+    /// Gusts exceeding the highest forecast gusts (or mean wind, if no gusts were forecast) by 10kt or more.
+    Gusts {
+        /// Observed gusts.
+        observed: Speed,
+        /// Highest forecast gusts, or mean wind if none were forecast.
+        highest: Speed,
+    },
+}
+
+impl Verification {
+    /// Checks every METAR issued by the station of the TAF during its validity. Other METARs and missing reports (NIL) are ignored.
+    #[must_use]
+    pub fn new(taf: &Taf, metars: &[Metar]) -> Self {
+        let checks = metars
+            .iter()
+            .filter(|metar| metar.icao_code == taf.station)
+            .filter(|metar| !metar.modifiers.contains(&ReportModifier::Nil))
+            .filter_map(|metar| {
+                let time = observation_time(metar)?;
+                (taf.valid_from <= time && time < taf.valid_to)
+                    .then(|| ObservationCheck::new(taf, time, &metar.fields))
+            })
+            .collect();
+        Verification {
+            station: taf.station.clone(),
+            checks,
+        }
+    }
+
+    /// Number of observations with at least one bust.
+    #[must_use]
+    pub fn bust_count(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| !check.busts.is_empty())
+            .count()
+    }
+
+    /// Renders a summary line followed by one line per observation, busts in red.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let summary = format!(
+            "{} of {} observations outside the forecast",
+            self.bust_count(),
+            self.checks.len()
+        );
+        let summary = if self.bust_count() == 0 {
+            summary.green()
+        } else {
+            summary.red()
+        };
+        let mut output: ColoredString =
+            format!("{} {summary}", self.station.bright_white().on_blue()).into();
+        for check in &self.checks {
+            output = format!("{output}\n{}", check.colourise()).into();
+        }
+        output
+    }
+}
+
+impl ObservationCheck {
+    /// Compares the fields observed at `time` to what the TAF forecast for that time.
+    #[must_use]
+    pub fn new(taf: &Taf, time: DateTime<Utc>, observed: &[WxField]) -> Self {
+        let alternatives = forecast_alternatives(taf, time);
+        let busts = [
+            visibility_bust(observed, &alternatives),
+            ceiling_bust(observed, &alternatives),
+            wind_bust(observed, &alternatives),
+            gust_bust(observed, &alternatives),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        ObservationCheck { time, busts }
+    }
+
+    /// Time of the observation followed by OK or its busts in red.
+    #[must_use]
+    pub fn colourise(&self) -> ColoredString {
+        let mut output: ColoredString = self.time.format("%d %H%MZ").to_string().white();
+        if self.busts.is_empty() {
+            return format!("{output} {}", "OK".green()).into();
+        }
+        for bust in &self.busts {
+            output = format!("{output} {}", bust.to_string().red()).into();
+        }
+        output
+    }
+}
+
+impl Display for Bust {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bust::Visibility {
+                observed,
+                lowest,
+                highest,
+            } => write!(
+                f,
+                "VIS {observed}{} (FCST {lowest}{}-{highest}{})",
+                observed.unit, lowest.unit, highest.unit
+            ),
+            Bust::Ceiling {
+                observed,
+                lowest,
+                highest,
+            } => write!(
+                f,
+                "CIG {} (FCST {}-{})",
+                format_ceiling(*observed),
+                format_ceiling(*lowest),
+                format_ceiling(*highest)
+            ),
+            Bust::Wind {
+                observed,
+                lowest,
+                highest,
+            } => write!(
+                f,
+                "WIND {}{} (FCST {}-{}{})",
+                observed.value(),
+                observed.unit,
+                lowest.value(),
+                highest.value(),
+                highest.unit
+            ),
+            Bust::Gusts { observed, highest } => write!(
+                f,
+                "GUSTS {}{} (FCST MAX {}{})",
+                observed.value(),
+                observed.unit,
+                highest.value(),
+                highest.unit
+            ),
+        }
+    }
+}
+
+fn format_ceiling(ceiling: Option<Altitude>) -> String {
+    match ceiling {
+        Some(altitude) => format!("{}FT", altitude.convert(AltitudeUnit::Ft).value()),
+        None => "NONE".to_string(),
+    }
+}
+
+fn observation_time(metar: &Metar) -> Option<DateTime<Utc>> {
+    metar.fields.iter().find_map(|field| match field {
+        WxField::TimeStamp(time) => Some(time.to_utc()),
+        _ => None,
+    })
+}
+
+fn band<T: Ord>(value: &T, thresholds: &[T]) -> usize {
+    thresholds
+        .iter()
+        .filter(|threshold| *threshold <= value)
+        .count()
+}
+
+fn visibility_bust(observed: &[WxField], alternatives: &[Vec<WxField>]) -> Option<Bust> {
+    let observed = visibility(observed)?;
+    let forecast: Vec<Distance> = alternatives
+        .iter()
+        .filter_map(|fields| visibility(fields))
+        .collect();
+    let lowest = *forecast.iter().min()?;
+    let highest = *forecast.iter().max()?;
+    let thresholds: Vec<Distance> = VISIBILITY_BANDS
+        .iter()
+        .map(|metres| Distance::new(*metres, DistanceUnit::M))
+        .collect();
+    let observed_band = band(&observed, &thresholds);
+    (observed_band < band(&lowest, &thresholds) || observed_band > band(&highest, &thresholds))
+        .then_some(Bust::Visibility {
+            observed,
+            lowest,
+            highest,
+        })
+}
+
+/// Checks the observed ceiling against the forecast. Observations without any cloud group (e.g. "//////" from an automated station) are not checked, as the absence of a ceiling is only reported by CAVOK, NSC, NCD, CLR or layers without BKN or OVC.
+fn ceiling_bust(observed: &[WxField], alternatives: &[Vec<WxField>]) -> Option<Bust> {
+    if !observed
+        .iter()
+        .any(|field| matches!(field, WxField::Cavok | WxField::Clouds(..)))
+    {
+        return None;
+    }
+    let thresholds: Vec<Altitude> = CEILING_BANDS
+        .iter()
+        .map(|feet| Altitude::new(*feet, AltitudeUnit::Ft))
+        .collect();
+    // No ceiling falls into the band above the highest threshold.
+    let ceiling_band = |ceiling: Option<Altitude>| {
+        ceiling.map_or(thresholds.len(), |ceiling| band(&ceiling, &thresholds))
+    };
+    let forecast: Vec<Option<Altitude>> =
+        alternatives.iter().map(|fields| ceiling(fields)).collect();
+    let lowest = *forecast
+        .iter()
+        .min_by_key(|ceiling| ceiling_band(**ceiling))?;
+    let highest = *forecast
+        .iter()
+        .max_by_key(|ceiling| ceiling_band(**ceiling))?;
+    let observed = ceiling(observed);
+    let observed_band = ceiling_band(observed);
+    (observed_band < ceiling_band(lowest) || observed_band > ceiling_band(highest)).then_some(
+        Bust::Ceiling {
+            observed,
+            lowest,
+            highest,
+        },
+    )
+}
+
+fn wind(fields: &[WxField]) -> Option<(Speed, Option<Speed>)> {
+    fields.iter().find_map(|field| match field {
+        WxField::Wind {
+            strength, gusts, ..
+        } => Some((*strength, *gusts)),
+        _ => None,
+    })
+}
+
+fn wind_bust(observed: &[WxField], alternatives: &[Vec<WxField>]) -> Option<Bust> {
+    let (observed, _) = wind(observed)?;
+    let forecast: Vec<Speed> = alternatives
+        .iter()
+        .filter_map(|fields| wind(fields).map(|(strength, _)| strength))
+        .collect();
+    let lowest = *forecast.iter().min()?;
+    let highest = *forecast.iter().max()?;
+    let tolerance = Speed::new(WIND_TOLERANCE, SpeedUnit::Kt);
+    (observed - highest >= tolerance || lowest - observed >= tolerance).then_some(Bust::Wind {
+        observed,
+        lowest,
+        highest,
+    })
+}
+
+fn gust_bust(observed: &[WxField], alternatives: &[Vec<WxField>]) -> Option<Bust> {
+    let observed = wind(observed)?.1?;
+    let highest = alternatives
+        .iter()
+        .filter_map(|fields| wind(fields).map(|(strength, gusts)| gusts.unwrap_or(strength)))
+        .max()?;
+    let tolerance = Speed::new(WIND_TOLERANCE, SpeedUnit::Kt);
+    (observed - highest >= tolerance).then_some(Bust::Gusts { observed, highest })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::taf::fixtures::taf;
+
+    fn metar(raw: &str) -> Metar {
+        Metar::from_raw_at(raw, Utc.with_ymd_and_hms(2024, 6, 23, 0, 0, 0).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verification_forecast_held() {
+        let metars = vec![
+            metar("EDDK 211250Z 27012KT 9999 SCT030 20/10 Q1015"),
+            metar("EDDK 220250Z 00000KT 0800 FG FEW030 10/10 Q1016"),
+            metar("EDDK 221350Z 30018G28KT 9999 BKN025 18/08 Q1012"),
+        ];
+        let verification = Verification::new(&taf(), &metars);
+        assert_eq!(verification.checks.len(), 3);
+        assert_eq!(verification.bust_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_verification_ignores_other_reports() {
+        let metars = vec![
+            metar("EDDL 211250Z 27012KT 9999 SCT030 20/10 Q1015"),
+            metar("EDDK 211050Z 27012KT 9999 SCT030 20/10 Q1015"),
+            metar("EDDK 221850Z 27012KT 9999 SCT030 20/10 Q1015"),
+        ];
+        assert!(Verification::new(&taf(), &metars).checks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verification_visibility_and_ceiling() {
+        let metars = vec![metar("EDDK 211450Z 27010KT 2500 BR BKN006 15/14 Q1015")];
+        let verification = Verification::new(&taf(), &metars);
+        let busts = &verification.checks[0].busts;
+        assert_eq!(busts.len(), 2);
+        assert_eq!(
+            busts[0],
+            Bust::Visibility {
+                observed: Distance::new(2500, DistanceUnit::M),
+                lowest: Distance::new(9999, DistanceUnit::M),
+                highest: Distance::new(9999, DistanceUnit::M),
+            }
+        );
+        assert_eq!(
+            busts[1],
+            Bust::Ceiling {
+                observed: Some(Altitude::new(600, AltitudeUnit::Ft)),
+                lowest: None,
+                highest: None,
+            }
+        );
+    }
+
+    fn ceiling_taf() -> Taf {
+        Taf::from_raw_at(
+            "TAF EDDK 211100Z 2112/2218 27010KT 9999 BKN012=",
+            Utc.with_ymd_and_hms(2024, 6, 21, 11, 0, 0).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verification_ignores_nil() {
+        let metars = vec![metar("EDDK 211250Z NIL")];
+        assert!(Verification::new(&ceiling_taf(), &metars).checks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verification_missing_clouds() {
+        let metars = vec![
+            metar("EDDK 211350Z AUTO 27010KT 9999 ////// 20/10 Q1015"),
+            metar("EDDK 211450Z 27010KT 9999 NSC 20/10 Q1015"),
+        ];
+        let verification = Verification::new(&ceiling_taf(), &metars);
+        assert_eq!(verification.checks.len(), 2);
+        assert!(verification.checks[0].busts.is_empty());
+        let expected = vec![Bust::Ceiling {
+            observed: None,
+            lowest: Some(Altitude::new(1200, AltitudeUnit::Ft)),
+            highest: Some(Altitude::new(1200, AltitudeUnit::Ft)),
+        }];
+        assert_eq!(verification.checks[1].busts, expected);
+    }
+
+    #[tokio::test]
+    async fn test_verification_wind() {
+        let metars = vec![metar("EDDK 221450Z 30028G45KT 9999 BKN020 15/10 Q1005")];
+        let verification = Verification::new(&taf(), &metars);
+        let busts = &verification.checks[0].busts;
+        assert_eq!(busts.len(), 2);
+        assert_eq!(busts[0].to_string(), "WIND 28KT (FCST 15-15KT)");
+        assert_eq!(busts[1].to_string(), "GUSTS 45KT (FCST MAX 25KT)");
+    }
+
+    #[tokio::test]
+    async fn test_band() {
+        assert_eq!(band(&100, &VISIBILITY_BANDS), 0);
+        assert_eq!(band(&800, &VISIBILITY_BANDS), 4);
+        assert_eq!(band(&9999, &VISIBILITY_BANDS), 7);
+    }
+
+    #[tokio::test]
+    async fn test_colourise() {
+        let metars = vec![
+            metar("EDDK 211250Z 27012KT 9999 SCT030 20/10 Q1015"),
+            metar("EDDK 211450Z 27010KT 9999 BKN006 15/14 Q1015"),
+        ];
+        let output = Verification::new(&taf(), &metars)
+            .colourise()
+            .clear()
+            .to_string();
+        let expected = "EDDK 1 of 2 observations outside the forecast\n21 1250Z OK\n21 1450Z CIG 600FT (FCST NONE-NONE)";
+        assert_eq!(output, expected);
+    }
+}